@@ -1,26 +1,58 @@
-use crate::state_machine::{PaymentStateApi, StateType, StateChangeEvent};
-use crate::state_machine::{AwaitingInfoAction, EmvPaymentAction, PaymentSuccessAction, PaymentType, EmvResult};
+use crate::state_machine::{PaymentStateApi, StateType, StateChangeEvent, StateEvent, StateAction, StateHook, EventSubscriber};
+use crate::state_machine::{AwaitingInfoAction, EmvPaymentAction, PaymentSuccessAction, PaymentType, PaymentInfo, EmvResult, DeclinedAction, PixPaymentAction};
+use crate::state_machine::config;
+use crate::money::Amount;
+use crate::state_machine::velocity::{VelocityRule, VelocityTracker};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
 /// API pública para o Flutter
 /// 
 /// Esta é a interface que será exposta via FFI para o Flutter
 pub struct RustPaymentApi {
     api: PaymentStateApi,
+    /// Autorizações recentes da sessão (regra anti teste de cartão)
+    velocity: Mutex<VelocityTracker>,
+    /// Tarefa de `set_auto_reset`, abortada ao reconfigurar
+    auto_reset: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl RustPaymentApi {
     /// Cria uma nova instância da API
     pub fn new() -> Self {
+        Self::from_state_api(PaymentStateApi::new())
+    }
+    
+    /// Retoma um fluxo salvo com `snapshot` (ex: app encerrado no meio do pagamento)
+    /// 
+    /// A regra de velocidade recomeça com o padrão.
+    pub fn restore(snapshot: String) -> Result<Self, String> {
+        PaymentStateApi::restore(&snapshot)
+            .map(Self::from_state_api)
+            .map_err(|e| e.to_string())
+    }
+    
+    fn from_state_api(api: PaymentStateApi) -> Self {
         Self {
-            api: PaymentStateApi::new(),
+            api,
+            velocity: Mutex::new(VelocityTracker::new(VelocityRule::default())),
+            auto_reset: Mutex::new(None),
         }
     }
     
+    /// Serializa o estado atual para retomar com `restore`
+    pub async fn snapshot(&self) -> Result<String, String> {
+        self.api.snapshot().await.map_err(|e| e.to_string())
+    }
+    
     /// Define o valor do pagamento
     pub async fn set_amount(&self, amount: f64) -> Result<String, String> {
         self.api
             .execute(AwaitingInfoAction::SetAmount { amount })
             .await
+            .map(|outcome| outcome.to_string())
             .map_err(|e| e.to_string())
     }
     
@@ -29,6 +61,27 @@ impl RustPaymentApi {
         self.api
             .execute(AwaitingInfoAction::SetPaymentType { payment_type })
             .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Define a gorjeta (>= 0), cobrada junto com o valor
+    pub async fn set_tip(&self, tip: f64) -> Result<String, String> {
+        self.api
+            .execute(AwaitingInfoAction::SetTip { tip })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Define se o fluxo exige processamento antes de concluir
+    /// 
+    /// Fluxos offline/Pix usam `false` para concluir diretamente.
+    pub async fn set_require_processing(&self, require_processing: bool) -> Result<String, String> {
+        self.api
+            .execute(AwaitingInfoAction::SetRequireProcessing { require_processing })
+            .await
+            .map(|outcome| outcome.to_string())
             .map_err(|e| e.to_string())
     }
     
@@ -37,48 +90,373 @@ impl RustPaymentApi {
         self.api
             .execute(AwaitingInfoAction::ConfirmInfo)
             .await
+            .map(|outcome| outcome.to_string())
             .map_err(|e| e.to_string())
     }
     
     /// Processa o pagamento EMV
+    /// 
+    /// Recusa com `velocity_exceeded` se a sessão já enviou autorizações
+    /// demais dentro da janela da regra de velocidade.
     pub async fn process_payment(&self) -> Result<String, String> {
+        self.within_velocity_rule(async {
+            self.api
+                .execute(EmvPaymentAction::ProcessPayment)
+                .await
+                .map(|outcome| outcome.to_string())
+                .map_err(|e| e.to_string())
+        })
+        .await
+    }
+    
+    /// Reserva uma vaga na regra de velocidade antes de `authorization`
+    /// 
+    /// A vaga é devolvida se a autorização falhar: só autorizações
+    /// enviadas contam para a regra.
+    async fn within_velocity_rule<T>(
+        &self,
+        authorization: impl Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let reserved_at = self.velocity_tracker().reserve().map_err(|e| e.to_string())?;
+        
+        let result = authorization.await;
+        if result.is_err() {
+            self.velocity_tracker().release(reserved_at);
+        }
+        result
+    }
+    
+    /// Configura a regra de velocidade (máximo de autorizações por janela)
+    pub fn set_velocity_rule(&self, max_authorizations: u32, window_secs: u64) {
+        self.velocity_tracker().set_rule(VelocityRule {
+            max_authorizations,
+            window: Duration::from_secs(window_secs),
+        });
+    }
+    
+    /// Define os limites do adquirente aceitos por `set_amount`
+    pub fn set_amount_limits(&self, min: f64, max: f64) -> Result<(), String> {
+        config::set_amount_limits(min, max).map_err(|e| e.to_string())
+    }
+    
+    /// Define o valor acima do qual cancelamentos exigem motivo
+    pub fn set_cancel_reason_threshold(&self, threshold: f64) -> Result<(), String> {
+        config::set_cancel_reason_threshold(threshold).map_err(|e| e.to_string())
+    }
+    
+    /// Define a janela (ms) após ProcessPayment em que cancelar ainda é permitido
+    pub fn set_cancel_grace_window(&self, window_ms: u64) -> Result<(), String> {
+        config::set_cancel_grace_window(Duration::from_millis(window_ms)).map_err(|e| e.to_string())
+    }
+    
+    /// Define o total acima do qual a aprovação exige `confirm_cardholder`
+    pub fn set_cardholder_confirmation_threshold(&self, threshold: f64) -> Result<(), String> {
+        config::set_cardholder_confirmation_threshold(threshold).map_err(|e| e.to_string())
+    }
+    
+    /// Volta para AwaitingInfo `after_secs` depois de cada sucesso (`None` desativa)
+    /// 
+    /// Substitui a configuração anterior.
+    pub async fn set_auto_reset(&self, after_secs: Option<u64>) {
+        let task = after_secs.map(|secs| self.api.enable_auto_reset(Duration::from_secs(secs)));
+        let previous = std::mem::replace(&mut *self.auto_reset.lock().unwrap_or_else(|e| e.into_inner()), task);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+    
+    /// Cancela pagamentos abandonados em EMVPayment após o prazo (`None` desativa)
+    pub fn set_emv_timeout(&self, timeout_secs: Option<u64>) {
+        self.api.set_emv_timeout(timeout_secs.map(Duration::from_secs));
+    }
+    
+    /// Falha automaticamente pagamentos parados no processamento (`None` desativa)
+    pub fn set_processing_timeout(&self, timeout_secs: Option<u64>) {
+        self.api.set_processing_timeout(timeout_secs.map(Duration::from_secs));
+    }
+    
+    /// Marca um evento (pelo `seq`) como obsoleto antes de ser consumido
+    pub async fn mark_superseded(&self, seq: u64) {
+        self.api.mark_superseded(seq).await
+    }
+    
+    fn velocity_tracker(&self) -> std::sync::MutexGuard<'_, VelocityTracker> {
+        self.velocity.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Executa um pagamento com cartão inteiro numa única chamada
+    /// 
+    /// Útil para terminais sem interação (ex: autoatendimento). Conta como
+    /// uma autorização na regra de velocidade e retorna o `PaymentSuccess`
+    /// em JSON; PIX é recusado.
+    pub async fn run_to_completion(
+        &self,
+        amount: f64,
+        payment_type: PaymentType,
+        tip: f64,
+        transaction_id: String,
+        authorization_code: String,
+    ) -> Result<String, String> {
+        let info = PaymentInfo {
+            amount: Amount::new(amount).map_err(|e| e.to_string())?,
+            payment_type,
+            tip: Amount::new(tip).map_err(|e| e.to_string())?,
+        };
+        let result = EmvResult::builder()
+            .transaction_id(transaction_id)
+            .authorization_code(authorization_code)
+            .build()
+            .map_err(|e| e.to_string())?;
+        
+        self.within_velocity_rule(async {
+            let success = self.api
+                .run_to_completion(info, || result)
+                .await
+                .map_err(|e| e.to_string())?;
+            serde_json::to_string(&success).map_err(|e| e.to_string())
+        })
+        .await
+    }
+    
+    /// Completa o pagamento com sucesso
+    pub async fn complete_payment(
+        &self,
+        transaction_id: String,
+        authorization_code: String,
+    ) -> Result<String, String> {
+        let result = EmvResult::builder()
+            .transaction_id(transaction_id)
+            .authorization_code(authorization_code)
+            .build()
+            .map_err(|e| e.to_string())?;
+        
         self.api
-            .execute(EmvPaymentAction::ProcessPayment)
+            .execute(EmvPaymentAction::CompletePayment { result: Some(result) })
             .await
+            .map(|outcome| outcome.to_string())
             .map_err(|e| e.to_string())
     }
     
-    /// Completa o pagamento com sucesso
-    pub async fn complete_payment(
+    /// Completa o pagamento aprovado pelo terminal sem contato com o emissor
+    pub async fn complete_offline_payment(
         &self,
         transaction_id: String,
         authorization_code: String,
     ) -> Result<String, String> {
-        let result = EmvResult {
-            transaction_id,
-            authorization_code,
-            timestamp: chrono::Utc::now().to_rfc3339(),
-        };
+        let result = EmvResult::builder()
+            .transaction_id(transaction_id)
+            .authorization_code(authorization_code)
+            .build()
+            .map_err(|e| e.to_string())?;
         
         self.api
-            .execute(EmvPaymentAction::CompletePayment { result })
+            .execute(EmvPaymentAction::CompleteOfflineStandIn { result: Some(result) })
             .await
+            .map(|outcome| outcome.to_string())
             .map_err(|e| e.to_string())
     }
     
     /// Cancela o pagamento atual
     pub async fn cancel_payment(&self) -> Result<String, String> {
         self.api
-            .execute(EmvPaymentAction::CancelPayment)
+            .execute(EmvPaymentAction::CancelPayment { reason: None })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Cancela o pagamento atual informando o motivo
+    /// 
+    /// Obrigatório para valores acima do limite de auditoria.
+    pub async fn cancel_payment_with_reason(&self, reason: String) -> Result<String, String> {
+        self.api
+            .execute(EmvPaymentAction::CancelPayment { reason: Some(reason) })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Registra uma falha de leitura do chip (fallback para tarja após 3)
+    pub async fn chip_read_failed(&self) -> Result<String, String> {
+        self.api
+            .execute(EmvPaymentAction::ChipReadFailed)
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Registra a confirmação explícita do portador (vendas de alto valor)
+    pub async fn confirm_cardholder(&self) -> Result<String, String> {
+        self.api
+            .execute(EmvPaymentAction::ConfirmCardholder)
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Registra a recusa da autorização pelo adquirente
+    pub async fn decline_payment(&self, reason: String) -> Result<String, String> {
+        self.api
+            .execute(EmvPaymentAction::DeclinePayment { reason })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Reembolsa (total ou parcialmente) um pagamento concluído
+    pub async fn refund_payment(&self, amount: f64) -> Result<String, String> {
+        self.api
+            .execute(PaymentSuccessAction::RefundPayment { amount })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Cobrança complementar no mesmo cartão após um pagamento concluído
+    pub async fn supplemental_charge(&self, amount: f64) -> Result<String, String> {
+        self.api
+            .execute(PaymentSuccessAction::SupplementalCharge { amount })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Ajusta a gorjeta de um pagamento concluído (dentro do prazo)
+    pub async fn adjust_tip(&self, new_tip: f64) -> Result<String, String> {
+        self.api
+            .execute(PaymentSuccessAction::AdjustTip { new_tip })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Tenta novamente após uma recusa (volta para AwaitingInfo preenchido)
+    pub async fn retry_payment(&self) -> Result<String, String> {
+        self.api
+            .execute(DeclinedAction::Retry)
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Reporta o progresso do processamento EMV (0–100)
+    pub async fn report_progress(&self, percent: u8) -> Result<(), String> {
+        self.api
+            .report_progress(percent)
             .await
             .map_err(|e| e.to_string())
     }
     
+    /// Atualizações de progresso pendentes (para a barra de progresso)
+    pub async fn progress_events(&self) -> Vec<u8> {
+        self.api.progress_events().await
+    }
+    
+    /// Linha do tempo do fluxo como JSON (overlay de debug / suporte)
+    pub async fn flow_timeline_json(&self) -> String {
+        self.api.flow_timeline_json().await
+    }
+    
+    /// Limita os buffers de eventos e de histórico (descarta os mais antigos)
+    pub async fn set_memory_budget(&self, max_events: usize, max_history: usize) {
+        self.api.set_memory_budget(max_events, max_history).await
+    }
+    
+    /// Histórico de transições da sessão, para reconstrução em disputas
+    pub async fn history(&self) -> Vec<StateChangeEvent> {
+        self.api.history().await
+    }
+    
+    /// Últimos eventos emitidos, do mais antigo ao mais recente (limitados por `set_memory_budget`)
+    pub async fn recent_events(&self) -> Vec<StateChangeEvent> {
+        self.api.recent_events().await
+    }
+    
+    /// Transação atual como JSON (estado e, após confirmar, `payment_info`)
+    pub async fn current_transaction_json(&self) -> Result<String, String> {
+        self.api.current_transaction_json().await.map_err(|e| e.to_string())
+    }
+    
+    /// Desfaz a última transição (ex: confirmação acidental)
+    pub async fn undo(&self) -> Result<String, String> {
+        self.api.undo().await.map_err(|e| e.to_string())
+    }
+    
+    /// Executa uma ação serializada como `StateAction`
+    /// 
+    /// Formato: `{"type":"AwaitingInfo","data":{"type":"SetAmount","data":{"amount":100.0}}}`.
+    /// Retorna um `ActionOutcome` em JSON; JSON malformado ou ação
+    /// incompatível com o estado atual resultam em erro. `ProcessPayment`
+    /// passa pela mesma regra de velocidade de `process_payment`.
+    pub async fn execute_json(&self, action_json: String) -> Result<String, String> {
+        let action: StateAction = serde_json::from_str(&action_json)
+            .map_err(|e| format!("Ação JSON inválida: {}", e))?;
+        let authorizes = matches!(action, StateAction::EmvPayment(EmvPaymentAction::ProcessPayment));
+        
+        let execution = async {
+            let outcome = self.api.execute_action(action).await.map_err(|e| e.to_string())?;
+            serde_json::to_string(&outcome).map_err(|e| e.to_string())
+        };
+        if authorizes {
+            self.within_velocity_rule(execution).await
+        } else {
+            execution.await
+        }
+    }
+    
+    /// Registra um efeito ao entrar em `state` (ver `register_state_hook` na C ABI)
+    pub(crate) fn on_enter(&self, state: StateType, hook: StateHook) {
+        self.api.on_enter(state, hook);
+    }
+    
+    /// Registra um efeito ao sair de `state` (ver `register_state_hook` na C ABI)
+    pub(crate) fn on_exit(&self, state: StateType, hook: StateHook) {
+        self.api.on_exit(state, hook);
+    }
+    
+    /// Passa a registrar mudanças de estado e ações rejeitadas (ex: toasts de erro)
+    pub async fn subscribe_state_events(&self) {
+        self.api.subscribe_state_events().await
+    }
+    
+    /// Para de registrar `StateEvent`
+    pub async fn unsubscribe_state_events(&self) {
+        self.api.unsubscribe_state_events().await
+    }
+    
+    /// `StateEvent` registrados desde a última leitura (vazio sem assinatura)
+    pub async fn pending_state_events(&self) -> Vec<StateEvent> {
+        self.api.pending_state_events().await
+    }
+    
+    /// Novo assinante das mudanças de estado (callback da C ABI)
+    pub(crate) fn subscribe_events(&self) -> EventSubscriber {
+        self.api.subscribe()
+    }
+    
     /// Retorna o estado atual
     pub async fn get_current_state(&self) -> StateType {
         self.api.current_state().await
     }
     
+    /// Ações válidas no estado atual (quais botões exibir)
+    pub async fn available_actions(&self) -> Vec<String> {
+        self.api.available_actions().await.unwrap_or_default()
+    }
+    
+    /// Indica se o fluxo terminou (exibir botão "nova venda")
+    pub async fn is_terminal_state(&self) -> bool {
+        self.api.is_terminal_state().await
+    }
+    
+    /// Obtém a descrição do estado atual, qualquer que seja ele
+    pub async fn get_description(&self) -> Result<String, String> {
+        self.api
+            .current_description()
+            .await
+            .map_err(|e| e.to_string())
+    }
+    
     /// Obtém descrição do estado AwaitingInfo
     pub async fn get_awaiting_info_description(&self) -> Result<String, String> {
         self.api
@@ -102,6 +480,23 @@ impl RustPaymentApi {
             .await
             .map_err(|e| e.to_string())
     }
+    
+    /// Payload "copia e cola" do QR code PIX
+    pub async fn get_pix_qr_payload(&self) -> Result<String, String> {
+        self.api
+            .pix_qr_payload()
+            .await
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Confirma o recebimento do PIX (identificador fim a fim do PSP)
+    pub async fn confirm_pix_received(&self, end_to_end_id: String) -> Result<String, String> {
+        self.api
+            .execute(PixPaymentAction::ConfirmPixReceived { end_to_end_id })
+            .await
+            .map(|outcome| outcome.to_string())
+            .map_err(|e| e.to_string())
+    }
 }
 
 impl Default for RustPaymentApi {
@@ -109,3 +504,252 @@ impl Default for RustPaymentApi {
         Self::new()
     }
 }
+
+impl Drop for RustPaymentApi {
+    fn drop(&mut self) {
+        if let Some(task) = self.auto_reset.get_mut().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod rust_payment_api_tests {
+    use super::*;
+
+    async fn confirm_card_payment(api: &RustPaymentApi, amount: f64) {
+        api.set_payment_type(PaymentType::Credit).await.unwrap();
+        api.set_amount(amount).await.unwrap();
+        api.confirm_info().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_amount_limits_apply_to_set_amount() {
+        let api = RustPaymentApi::new();
+        assert!(api.set_amount_limits(10.0, 5.0).is_err());
+        assert!(api.set_amount_limits(-1.0, 5.0).is_err());
+        assert!(api.set_amount_limits(1.0, f64::INFINITY).is_err());
+        api.set_amount_limits(5.0, 200.0).unwrap();
+        
+        let error = api.set_amount(4.99).await.unwrap_err();
+        assert!(error.contains("abaixo do limite permitido (R$ 5.00)"));
+        let error = api.set_amount(200.01).await.unwrap_err();
+        assert!(error.contains("acima do limite permitido (R$ 200.00)"));
+        api.set_amount(200.0).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_cancel_reason_threshold_applies_to_next_cancel() {
+        let api = RustPaymentApi::new();
+        assert!(api.set_cancel_reason_threshold(f64::NAN).is_err());
+        assert!(api.set_cancel_reason_threshold(-1.0).is_err());
+        api.set_cancel_reason_threshold(50.0).unwrap();
+        confirm_card_payment(&api, 100.0).await;
+        
+        let error = api.cancel_payment().await.unwrap_err();
+        assert!(error.contains("motivo obrigatório"));
+        api.cancel_payment_with_reason("Cliente desistiu".to_string()).await.unwrap();
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+    }
+    
+    #[tokio::test]
+    async fn test_cancel_grace_window_is_read_at_cancel_time() {
+        let api = RustPaymentApi::new();
+        assert!(api.set_cancel_grace_window(60_000).is_err());
+        api.set_cancel_grace_window(0).unwrap();
+        confirm_card_payment(&api, 100.0).await;
+        api.process_payment().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        
+        let error = api.cancel_payment().await.unwrap_err();
+        assert!(error.contains("Janela de cancelamento expirada"));
+        
+        api.set_cancel_grace_window(30_000).unwrap();
+        api.cancel_payment().await.unwrap();
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+    }
+    
+    #[tokio::test]
+    async fn test_cardholder_confirmation_threshold_counts_the_tip() {
+        let api = RustPaymentApi::new();
+        assert!(api.set_cardholder_confirmation_threshold(f64::NAN).is_err());
+        api.set_cardholder_confirmation_threshold(100.0).unwrap();
+        api.set_tip(10.0).await.unwrap();
+        confirm_card_payment(&api, 95.0).await;
+        api.process_payment().await.unwrap();
+        
+        let error = api.complete_payment("TXN".to_string(), "AUTH".to_string()).await.unwrap_err();
+        assert!(error.contains("confirmação do portador"));
+        api.confirm_cardholder().await.unwrap();
+        api.complete_payment("TXN".to_string(), "AUTH".to_string()).await.unwrap();
+        assert_eq!(api.get_current_state().await, StateType::PaymentSuccess);
+    }
+    
+    #[tokio::test]
+    async fn test_recent_events_respect_memory_budget() {
+        let api = RustPaymentApi::new();
+        api.set_memory_budget(2, 10).await;
+        confirm_card_payment(&api, 100.0).await;
+        api.cancel_payment().await.unwrap();
+        confirm_card_payment(&api, 50.0).await;
+        
+        let seqs: Vec<u64> = api.recent_events().await.iter().map(|event| event.seq).collect();
+        assert_eq!(seqs, [2, 3]);
+    }
+    
+    #[tokio::test]
+    async fn test_current_transaction_json_includes_payment_info_once_confirmed() {
+        let api = RustPaymentApi::new();
+        let transaction: serde_json::Value =
+            serde_json::from_str(&api.current_transaction_json().await.unwrap()).unwrap();
+        assert_eq!(transaction["state"], "AwaitingInfo");
+        assert!(transaction.get("payment_info").is_none());
+        
+        api.set_tip(5.0).await.unwrap();
+        confirm_card_payment(&api, 100.0).await;
+        let transaction: serde_json::Value =
+            serde_json::from_str(&api.current_transaction_json().await.unwrap()).unwrap();
+        assert_eq!(transaction["state"], "EMVPayment");
+        assert_eq!(transaction["payment_info"]["amount"], 100.0);
+        assert_eq!(transaction["payment_info"]["tip"], 5.0);
+        assert_eq!(transaction["payment_info"]["payment_type"], "Credit");
+    }
+    
+    #[tokio::test]
+    async fn test_restore_resumes_from_snapshot() {
+        let api = RustPaymentApi::new();
+        api.set_tip(2.5).await.unwrap();
+        confirm_card_payment(&api, 80.0).await;
+        let snapshot = api.snapshot().await.unwrap();
+        
+        let restored = RustPaymentApi::restore(snapshot).unwrap();
+        assert_eq!(restored.get_current_state().await, StateType::EMVPayment);
+        restored.process_payment().await.unwrap();
+        restored.complete_payment("TXN-R".to_string(), "AUTH".to_string()).await.unwrap();
+        let transaction: serde_json::Value =
+            serde_json::from_str(&restored.current_transaction_json().await.unwrap()).unwrap();
+        assert_eq!(transaction["payment_info"]["tip"], 2.5);
+        
+        assert!(RustPaymentApi::restore("{}".to_string()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_flow_timeline_json_lists_transitions() {
+        let api = RustPaymentApi::new();
+        confirm_card_payment(&api, 100.0).await;
+
+        let timeline: serde_json::Value = serde_json::from_str(&api.flow_timeline_json().await).unwrap();
+        let timeline = timeline.as_array().unwrap();
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0]["from"], "AwaitingInfo");
+        assert_eq!(timeline[0]["to"], "EMVPayment");
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_emv_timeout_cancels_abandoned_payment() {
+        let api = RustPaymentApi::new();
+        api.set_emv_timeout(Some(30));
+        confirm_card_payment(&api, 100.0).await;
+        
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(api.get_current_state().await, StateType::EMVPayment);
+        
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+    }
+    
+    #[tokio::test]
+    async fn test_run_to_completion_counts_toward_velocity_rule() {
+        let api = RustPaymentApi::new();
+        api.set_velocity_rule(1, 60);
+        
+        let success: serde_json::Value = serde_json::from_str(&api.run_to_completion(
+            120.0,
+            PaymentType::Debit,
+            12.0,
+            "TXN-RUN".to_string(),
+            "AUTH-RUN".to_string(),
+        ).await.unwrap()).unwrap();
+        assert_eq!(success["result"]["transaction_id"], "TXN-RUN");
+        assert_eq!(success["payment_info"]["tip"], 12.0);
+        assert_eq!(api.get_current_state().await, StateType::PaymentSuccess);
+        
+        api.execute_json(r#"{"type":"PaymentSuccess","data":{"type":"Reset"}}"#.to_string()).await.unwrap();
+        let error = api.run_to_completion(
+            10.0,
+            PaymentType::Debit,
+            0.0,
+            "TXN-2".to_string(),
+            "AUTH-2".to_string(),
+        ).await.unwrap_err();
+        assert!(error.contains("velocity_exceeded"));
+    }
+    
+    #[tokio::test]
+    async fn test_run_to_completion_rejects_pix_without_using_velocity_slot() {
+        let api = RustPaymentApi::new();
+        api.set_velocity_rule(1, 60);
+        
+        assert!(api.run_to_completion(10.0, PaymentType::Pix, 0.0, "TXN".to_string(), "AUTH".to_string()).await.is_err());
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+        api.run_to_completion(10.0, PaymentType::Credit, 0.0, "TXN".to_string(), "AUTH".to_string()).await.unwrap();
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_reset_can_be_replaced_and_disabled() {
+        let api = RustPaymentApi::new();
+        api.set_auto_reset(Some(5)).await;
+        api.set_auto_reset(Some(60)).await;
+        
+        api.run_to_completion(10.0, PaymentType::Debit, 0.0, "TXN-1".to_string(), "AUTH".to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        // O timer de 5s foi substituído pelo de 60s
+        assert_eq!(api.get_current_state().await, StateType::PaymentSuccess);
+        tokio::time::sleep(Duration::from_secs(55)).await;
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+        
+        api.set_auto_reset(None).await;
+        api.run_to_completion(10.0, PaymentType::Debit, 0.0, "TXN-2".to_string(), "AUTH".to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(120)).await;
+        assert_eq!(api.get_current_state().await, StateType::PaymentSuccess);
+    }
+    
+    #[tokio::test]
+    async fn test_execute_json_dispatches_actions() {
+        let api = RustPaymentApi::new();
+        
+        let outcome: serde_json::Value = serde_json::from_str(&api.execute_json(
+            r#"{"type":"AwaitingInfo","data":{"type":"SetAmount","data":{"amount":100.0}}}"#.to_string()
+        ).await.unwrap()).unwrap();
+        assert_eq!(outcome["from_state"], "AwaitingInfo");
+        assert_eq!(outcome["state"], "AwaitingInfo");
+        assert_eq!(outcome["transitioned"], false);
+        
+        api.execute_json(
+            r#"{"type":"AwaitingInfo","data":{"type":"SetPaymentType","data":{"payment_type":"Credit"}}}"#.to_string()
+        ).await.unwrap();
+        let outcome: serde_json::Value = serde_json::from_str(&api.execute_json(
+            r#"{"type":"AwaitingInfo","data":{"type":"ConfirmInfo"}}"#.to_string()
+        ).await.unwrap()).unwrap();
+        assert_eq!(outcome["from_state"], "AwaitingInfo");
+        assert_eq!(outcome["state"], "EMVPayment");
+        assert_eq!(outcome["transitioned"], true);
+        assert_eq!(api.get_current_state().await, StateType::EMVPayment);
+    }
+    
+    #[tokio::test]
+    async fn test_execute_json_rejects_invalid_input() {
+        let api = RustPaymentApi::new();
+        
+        let error = api.execute_json("{\"type\":\"AwaitingInfo\"".to_string()).await.unwrap_err();
+        assert!(error.contains("Ação JSON inválida"));
+        
+        let error = api.execute_json(r#"{"type":"Desconhecido","data":{}}"#.to_string()).await;
+        assert!(error.is_err());
+        
+        // Ação válida, mas de outro estado
+        let error = api.execute_json(r#"{"type":"EmvPayment","data":{"type":"ProcessPayment"}}"#.to_string()).await;
+        assert!(error.is_err());
+        assert_eq!(api.get_current_state().await, StateType::AwaitingInfo);
+    }
+}
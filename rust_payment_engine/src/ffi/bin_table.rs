@@ -0,0 +1,242 @@
+//! Tabela de BINs para roteamento por tipo de conta
+//!
+//! Alguns BINs são exclusivamente débito ou crédito e o roteamento
+//! (single/dual message) muda. A tabela padrão é apenas demonstrativa e
+//! pode ser substituída em runtime via `set_bin_table`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::os::raw::c_char;
+use std::sync::{OnceLock, RwLock};
+
+use super::{into_c_string, json_result, read_c_str};
+use crate::state_machine::PaymentType;
+
+/// Tipo de conta associado a um BIN
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    Credit,
+    Debit,
+    Prepaid,
+    Unknown,
+}
+
+impl AccountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountType::Credit => "credit",
+            AccountType::Debit => "debit",
+            AccountType::Prepaid => "prepaid",
+            AccountType::Unknown => "unknown",
+        }
+    }
+}
+
+/// Regra da tabela: prefixo do PAN -> tipo de conta
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BinRule {
+    prefix: String,
+    account_type: AccountType,
+}
+
+/// Tabela padrão (BINs de teste públicos)
+fn default_rules() -> Vec<BinRule> {
+    [
+        ("411111", AccountType::Credit),
+        ("401288", AccountType::Credit),
+        ("555555", AccountType::Credit),
+        ("400551", AccountType::Debit),
+        ("520082", AccountType::Debit),
+        ("450875", AccountType::Debit),
+        ("537310", AccountType::Prepaid),
+    ]
+    .into_iter()
+    .map(|(prefix, account_type)| BinRule {
+        prefix: prefix.to_string(),
+        account_type,
+    })
+    .collect()
+}
+
+fn bin_table() -> &'static RwLock<Vec<BinRule>> {
+    static TABLE: OnceLock<RwLock<Vec<BinRule>>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(default_rules()))
+}
+
+/// Classifica um PAN pelo prefixo mais longo presente na tabela
+pub fn account_type_for(card_number: &str) -> AccountType {
+    let digits: String = card_number
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .take(19)
+        .collect();
+    let table = bin_table().read().unwrap_or_else(|e| e.into_inner());
+    table
+        .iter()
+        .filter(|rule| digits.starts_with(&rule.prefix))
+        .max_by_key(|rule| rule.prefix.len())
+        .map_or(AccountType::Unknown, |rule| rule.account_type)
+}
+
+/// Aviso quando o tipo de pagamento selecionado não combina com o BIN
+///
+/// Retorna `None` quando compatível ou quando o BIN é desconhecido.
+pub fn account_type_mismatch(payment_type: &PaymentType, account_type: AccountType) -> Option<String> {
+    match (payment_type, account_type) {
+        (PaymentType::Debit, AccountType::Credit) => {
+            Some("Débito selecionado, mas o cartão é somente crédito".to_string())
+        }
+        (PaymentType::Credit, AccountType::Debit | AccountType::Prepaid) => Some(format!(
+            "Crédito selecionado, mas o cartão é {}",
+            account_type.as_str()
+        )),
+        _ => None,
+    }
+}
+
+/// Resultado da checagem cruzada para o Dart
+#[derive(Debug, Serialize)]
+struct MismatchCheck {
+    account_type: AccountType,
+    warning: Option<String>,
+}
+
+/// Converte o código C de `PaymentType` (0=Debit, 1=Credit, 2=Verification, 3=Pix)
+pub(crate) fn payment_type_from_code(code: i32) -> Result<PaymentType> {
+    match code {
+        0 => Ok(PaymentType::Debit),
+        1 => Ok(PaymentType::Credit),
+        2 => Ok(PaymentType::Verification),
+        3 => Ok(PaymentType::Pix),
+        _ => Err(anyhow::anyhow!("Tipo de pagamento inválido: {}", code)),
+    }
+}
+
+/// Retorna o tipo de conta do cartão: "credit", "debit", "prepaid" ou "unknown"
+///
+/// # Safety
+/// `card_number` deve ser nulo ou uma string C válida. O retorno deve ser
+/// liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn account_type_from_bin(card_number: *const c_char) -> *mut c_char {
+    let account_type = read_c_str(card_number).map_or(AccountType::Unknown, account_type_for);
+    into_c_string(account_type.as_str().to_string())
+}
+
+/// Checa o tipo de pagamento selecionado contra o BIN antes do ConfirmInfo
+///
+/// `payment_type` segue a ordem de `PaymentType` (0=Debit, 1=Credit,
+/// 2=Verification, 3=Pix). Retorna `{ "account_type", "warning" }`.
+///
+/// # Safety
+/// `card_number` deve ser nulo ou uma string C válida. O retorno deve ser
+/// liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn check_account_type(card_number: *const c_char, payment_type: i32) -> *mut c_char {
+    let card_number = read_c_str(card_number);
+    json_result(payment_type_from_code(payment_type).map(|payment_type| {
+        let account_type = card_number.map_or(AccountType::Unknown, account_type_for);
+        MismatchCheck {
+            account_type,
+            warning: account_type_mismatch(&payment_type, account_type),
+        }
+    }))
+}
+
+fn parse_rules(json: Option<&str>) -> Result<Vec<BinRule>> {
+    let json = json.ok_or_else(|| anyhow::anyhow!("JSON não informado"))?;
+    let rules: Vec<BinRule> = serde_json::from_str(json)?;
+    if let Some(rule) = rules
+        .iter()
+        .find(|r| r.prefix.is_empty() || !r.prefix.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(anyhow::anyhow!("Prefixo inválido: {:?}", rule.prefix));
+    }
+    Ok(rules)
+}
+
+/// Substitui a tabela de BINs
+///
+/// `json` é um array `[{ "prefix": "4111", "account_type": "debit" }, ...]`.
+/// Retorna 0 em sucesso ou -1 se o JSON for inválido (tabela mantida).
+///
+/// # Safety
+/// `json` deve ser nulo ou uma string C válida.
+#[no_mangle]
+pub unsafe extern "C" fn set_bin_table(json: *const c_char) -> i32 {
+    match parse_rules(read_c_str(json)) {
+        Ok(rules) => {
+            *bin_table().write().unwrap_or_else(|e| e.into_inner()) = rules;
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Restaura a tabela de BINs padrão
+#[no_mangle]
+pub extern "C" fn reset_bin_table() {
+    *bin_table().write().unwrap_or_else(|e| e.into_inner()) = default_rules();
+}
+
+#[cfg(test)]
+mod bin_table_tests {
+    use super::*;
+    use crate::ffi::test_support::{take_json, take_string};
+    use std::ffi::CString;
+    use std::sync::Mutex;
+
+    /// Serializa os testes que dependem da tabela global
+    static TABLE_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lookup(card_number: &str) -> String {
+        let input = CString::new(card_number).unwrap();
+        take_string(unsafe { account_type_from_bin(input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_debit_bin() {
+        let _guard = TABLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(lookup("4005 5192 0000 0004"), "debit");
+    }
+
+    #[test]
+    fn test_credit_and_unknown_bins() {
+        let _guard = TABLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(lookup("4111111111111111"), "credit");
+        assert_eq!(lookup("6011000990139424"), "unknown");
+        assert_eq!(
+            take_string(unsafe { account_type_from_bin(std::ptr::null()) }),
+            "unknown"
+        );
+    }
+
+    #[test]
+    fn test_mismatch_debit_selected_with_credit_only_bin() {
+        let _guard = TABLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let card = CString::new("4111111111111111").unwrap();
+
+        let result = take_json(unsafe { check_account_type(card.as_ptr(), 0) });
+        assert_eq!(result["account_type"], "credit");
+        assert!(result["warning"].as_str().unwrap().contains("somente crédito"));
+
+        let result = take_json(unsafe { check_account_type(card.as_ptr(), 1) });
+        assert!(result["warning"].is_null());
+    }
+
+    #[test]
+    fn test_set_bin_table_overrides_and_validates() {
+        let _guard = TABLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let table = CString::new(r#"[{"prefix":"4111","account_type":"prepaid"}]"#).unwrap();
+        assert_eq!(unsafe { set_bin_table(table.as_ptr()) }, 0);
+        assert_eq!(lookup("4111111111111111"), "prepaid");
+
+        let invalid = CString::new(r#"[{"prefix":"41a","account_type":"debit"}]"#).unwrap();
+        assert_eq!(unsafe { set_bin_table(invalid.as_ptr()) }, -1);
+        assert_eq!(lookup("4111111111111111"), "prepaid");
+
+        reset_bin_table();
+        assert_eq!(lookup("4111111111111111"), "credit");
+    }
+}
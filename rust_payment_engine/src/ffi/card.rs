@@ -0,0 +1,431 @@
+//! Funções de dados de cartão (PCI-DSS)
+//!
+//! Nenhuma função deste módulo retorna o PAN completo: sempre mascarado
+//! ou removido.
+
+use anyhow::Result;
+use rand::Rng;
+use serde::Serialize;
+use serde_json::Value;
+use std::os::raw::c_char;
+
+use super::{into_c_string, json_result, read_c_str};
+
+/// Mascara um PAN mantendo os 6 primeiros e 4 últimos dígitos
+///
+/// Caracteres não numéricos são descartados. PANs curtos demais para
+/// manter BIN e final (menos de 13 dígitos) são totalmente mascarados.
+pub(crate) fn mask_pan(card_number: &str) -> String {
+    let digits: Vec<char> = card_number.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 13 {
+        return "*".repeat(digits.len());
+    }
+    let hidden = digits.len() - 10;
+    digits[..6]
+        .iter()
+        .chain(std::iter::repeat_n(&'*', hidden))
+        .chain(digits[digits.len() - 4..].iter())
+        .collect()
+}
+
+/// Normaliza o nome de um campo para comparação (minúsculas, sem separadores)
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Campos que carregam o PAN e devem ser mascarados
+fn is_pan_field(key: &str) -> bool {
+    matches!(key, "pan" | "cardpan" | "cardno" | "primaryaccountnumber" | "numerocartao")
+        || key.contains("cardnumber")
+}
+
+/// Campos de código de segurança que nunca podem ser registrados
+fn is_cvv_field(key: &str) -> bool {
+    key.contains("cvv") || key.contains("cvc") || matches!(key, "cid" | "securitycode")
+}
+
+/// Remove/mascara recursivamente os dados sensíveis de um valor JSON
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !is_cvv_field(&normalize_key(key)));
+            for (key, field) in map.iter_mut() {
+                if is_pan_field(&normalize_key(key)) {
+                    let raw = match &*field {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    *field = Value::String(mask_pan(&raw));
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+fn redact_json(json: Option<&str>) -> Result<Value> {
+    let json = json.ok_or_else(|| anyhow::anyhow!("JSON não informado"))?;
+    let mut value: Value = serde_json::from_str(json)?;
+    redact_value(&mut value);
+    Ok(value)
+}
+
+/// Sanitiza um registro de transação antes de ir para o log
+///
+/// Campos de PAN (`card_number`, `pan`, ...) são mascarados e campos de
+/// código de segurança (`cvv`, `cvc`, ...) são removidos, em qualquer nível
+/// do JSON. Os demais campos passam intactos.
+///
+/// # Safety
+/// `json` deve ser nulo ou uma string C válida. O retorno deve ser liberado
+/// com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn redact_transaction(json: *const c_char) -> *mut c_char {
+    match redact_json(read_c_str(json)) {
+        Ok(value) => into_c_string(value.to_string()),
+        Err(e) => json_result::<()>(Err(e)),
+    }
+}
+
+/// Mascara um PAN para o comprovante, ex: "411111******1111"
+///
+/// Mantém os 6 primeiros e 4 últimos dígitos após descartar não numéricos.
+/// PAN curto demais é totalmente mascarado; ponteiro nulo ou UTF-8 inválido
+/// resultam em string vazia.
+///
+/// # Safety
+/// `card_number` deve ser nulo ou uma string C válida. O retorno deve ser
+/// liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn mask_card_number(card_number: *const c_char) -> *mut c_char {
+    into_c_string(read_c_str(card_number).map(mask_pan).unwrap_or_default())
+}
+
+/// Verifica o dígito verificador (Luhn) de uma sequência de dígitos
+pub(crate) fn luhn_valid(digits: &str) -> bool {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .map(|b| u32::from(b - b'0'))
+        .enumerate()
+        .map(|(idx, d)| match (idx % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Calcula o dígito verificador de Luhn para `payload`
+fn luhn_check_digit(payload: &str) -> char {
+    (b'0'..=b'9')
+        .map(char::from)
+        .find(|d| luhn_valid(&format!("{}{}", payload, d)))
+        .unwrap_or('0')
+}
+
+/// Gera `prefix` + dígitos aleatórios + dígito verificador, com `length` dígitos
+///
+/// O chamador garante que `prefix` é numérico e menor que `length`.
+pub(crate) fn luhn_number(prefix: &str, length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    let mut payload = prefix.to_string();
+    while payload.len() < length - 1 {
+        payload.push(char::from(b'0' + rng.gen_range(0..10u8)));
+    }
+    let check_digit = luhn_check_digit(&payload);
+    payload.push(check_digit);
+    payload
+}
+
+/// Bandeiras reconhecidas, na ordem dos códigos da C ABI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CardBrand {
+    Visa,
+    Mastercard,
+    Amex,
+    Elo,
+    Hipercard,
+}
+
+/// Prefixos Elo mais comuns (alguns começam com 4 ou 5, então têm prioridade)
+const ELO_PREFIXES: &[&str] = &["401178", "438935", "451416", "457631", "504175", "506699", "636297", "636368"];
+
+impl CardBrand {
+    fn from_code(code: i32) -> Result<Self> {
+        match code {
+            0 => Ok(Self::Visa),
+            1 => Ok(Self::Mastercard),
+            2 => Ok(Self::Amex),
+            3 => Ok(Self::Elo),
+            4 => Ok(Self::Hipercard),
+            _ => Err(anyhow::anyhow!("Bandeira inválida: {}", code)),
+        }
+    }
+
+    /// Prefixo e comprimento usados nos cartões de teste
+    fn test_card_format(self) -> (&'static str, usize) {
+        match self {
+            // Prefixo longo: "4" sozinho pode sortear um prefixo Elo (ex: 401178)
+            Self::Visa => ("4111", 16),
+            Self::Mastercard => ("55", 16),
+            Self::Amex => ("37", 15),
+            Self::Elo => ("636368", 16),
+            Self::Hipercard => ("606282", 16),
+        }
+    }
+
+    /// Identifica a bandeira pelo prefixo do PAN (apenas dígitos)
+    fn from_pan(pan: &str) -> Option<Self> {
+        let prefix = |len: usize| pan.get(..len).and_then(|p| p.parse::<u32>().ok());
+        if ELO_PREFIXES.iter().any(|p| pan.starts_with(p)) {
+            Some(Self::Elo)
+        } else if pan.starts_with("606282") {
+            Some(Self::Hipercard)
+        } else if pan.starts_with("34") || pan.starts_with("37") {
+            Some(Self::Amex)
+        } else if matches!(prefix(2), Some(51..=55)) || matches!(prefix(4), Some(2221..=2720)) {
+            Some(Self::Mastercard)
+        } else if pan.starts_with('4') {
+            Some(Self::Visa)
+        } else {
+            None
+        }
+    }
+}
+
+/// Gera um número de cartão de teste válido (Luhn) para a bandeira
+///
+/// `brand_code`: 0=Visa, 1=Mastercard, 2=Amex, 3=Elo, 4=Hipercard. Retorna
+/// o número (apenas dígitos, nunca um PAN real conhecido) ou `{"error": ...}`.
+/// Liberar com `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn generate_test_card(brand_code: i32) -> *mut c_char {
+    match CardBrand::from_code(brand_code) {
+        Ok(brand) => {
+            let (prefix, length) = brand.test_card_format();
+            into_c_string(luhn_number(prefix, length))
+        }
+        Err(e) => json_result::<()>(Err(e)),
+    }
+}
+
+/// Bandeira do cartão pelo prefixo, nos códigos de `generate_test_card`
+///
+/// Caracteres não numéricos são descartados. Retorna -1 para bandeira
+/// desconhecida, ponteiro nulo ou UTF-8 inválido.
+///
+/// # Safety
+/// `card_number` deve ser nulo ou uma string C válida.
+#[no_mangle]
+pub unsafe extern "C" fn card_brand(card_number: *const c_char) -> i32 {
+    read_c_str(card_number)
+        .map(|card| card.chars().filter(|c| c.is_ascii_digit()).collect::<String>())
+        .and_then(|digits| CardBrand::from_pan(&digits))
+        .map_or(-1, |brand| brand as i32)
+}
+
+/// Dados extraídos da trilha 2 (PAN já mascarado)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct Track2Data {
+    pan: String,
+    /// Validade no formato da trilha (`YYMM`)
+    expiry: String,
+    service_code: String,
+}
+
+/// Interpreta uma trilha 2 no formato `;PAN=YYMMSSS<discricionário>?`
+///
+/// Sentinelas de início (`;`) e fim (`?`) são opcionais; o LRC após o `?`
+/// é ignorado.
+fn parse_track2_str(track: &str) -> Result<Track2Data> {
+    let track = track.trim();
+    let track = track.strip_prefix(';').unwrap_or(track);
+    let track = track.split('?').next().unwrap_or_default();
+
+    let (pan, rest) = track
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Trilha 2 sem separador '='"))?;
+    if !(12..=19).contains(&pan.len()) || !pan.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow::anyhow!("PAN inválido na trilha 2"));
+    }
+    if !luhn_valid(pan) {
+        return Err(anyhow::anyhow!("PAN falhou na verificação de Luhn"));
+    }
+
+    let fixed = rest.get(..7).filter(|f| f.chars().all(|c| c.is_ascii_digit()));
+    let fixed = fixed.ok_or_else(|| anyhow::anyhow!("Validade/código de serviço inválidos na trilha 2"))?;
+    let (expiry, service_code) = fixed.split_at(4);
+    let month: u32 = expiry[2..].parse()?;
+    if !(1..=12).contains(&month) {
+        return Err(anyhow::anyhow!("Mês de validade inválido: {}", month));
+    }
+
+    Ok(Track2Data {
+        pan: mask_pan(pan),
+        expiry: expiry.to_string(),
+        service_code: service_code.to_string(),
+    })
+}
+
+/// Valida e decompõe a trilha 2 de uma tarja magnética (fluxo swipe)
+///
+/// Retorna `{ "pan", "expiry", "service_code" }` ou `{"error": ...}` para
+/// trilhas malformadas ou PAN inválido (Luhn). Seguindo a regra do módulo,
+/// o `pan` retorna mascarado.
+///
+/// # Safety
+/// `track` deve ser nulo ou uma string C válida. O retorno deve ser liberado
+/// com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn parse_track2(track: *const c_char) -> *mut c_char {
+    let track = read_c_str(track).ok_or_else(|| anyhow::anyhow!("Trilha não informada"));
+    json_result(track.and_then(parse_track2_str))
+}
+
+#[cfg(test)]
+mod card_tests {
+    use super::*;
+    use crate::ffi::test_support::{take_json, take_string};
+    use std::ffi::CString;
+
+    fn redact(json: &str) -> Value {
+        let input = CString::new(json).unwrap();
+        take_json(unsafe { redact_transaction(input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_mask_pan() {
+        assert_eq!(mask_pan("4111 1111 1111 1111"), "411111******1111");
+        assert_eq!(mask_pan("378282246310005"), "378282*****0005");
+        assert_eq!(mask_pan("1234"), "****");
+    }
+
+    fn mask(card_number: &str) -> String {
+        let input = CString::new(card_number).unwrap();
+        take_string(unsafe { mask_card_number(input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_mask_card_number_visa_and_amex() {
+        assert_eq!(mask("4111-1111-1111-1111"), "411111******1111");
+        assert_eq!(mask("3782 822463 10005"), "378282*****0005");
+    }
+
+    #[test]
+    fn test_mask_card_number_short_and_null() {
+        assert_eq!(mask("12345"), "*****");
+        assert_eq!(mask("sem dígitos"), "");
+        assert_eq!(take_string(unsafe { mask_card_number(std::ptr::null()) }), "");
+    }
+
+    #[test]
+    fn test_redact_masks_pan_and_removes_cvv() {
+        let result = redact(
+            r#"{"id":"TXN-1","amount":100.5,"card_number":"4111111111111111","cvv":"123"}"#,
+        );
+
+        assert_eq!(result["card_number"], "411111******1111");
+        assert!(result.get("cvv").is_none());
+        assert_eq!(result["id"], "TXN-1");
+        assert_eq!(result["amount"], 100.5);
+    }
+
+    #[test]
+    fn test_redact_matches_nested_and_variant_field_names() {
+        let result = redact(
+            r#"{"payer":{"cardNumber":5555555555554444,"CVC2":"999"},"items":[{"PAN":"4012888888881881"}]}"#,
+        );
+
+        assert_eq!(result["payer"]["cardNumber"], "555555******4444");
+        assert!(result["payer"].get("CVC2").is_none());
+        assert_eq!(result["items"][0]["PAN"], "401288******1881");
+    }
+
+    #[test]
+    fn test_redact_rejects_invalid_json() {
+        let result = redact("{card_number:");
+        assert!(result.get("error").is_some());
+    }
+
+    #[test]
+    fn test_generate_test_card_round_trip_per_brand() {
+        let brands = [
+            (0, CardBrand::Visa, 16),
+            (1, CardBrand::Mastercard, 16),
+            (2, CardBrand::Amex, 15),
+            (3, CardBrand::Elo, 16),
+            (4, CardBrand::Hipercard, 16),
+        ];
+        for (code, brand, length) in brands {
+            for _ in 0..10 {
+                let card = take_string(generate_test_card(code));
+                assert_eq!(card.len(), length, "{}", card);
+                assert!(luhn_valid(&card), "{}", card);
+                assert_eq!(CardBrand::from_pan(&card), Some(brand), "{}", card);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_test_card_rejects_unknown_brand() {
+        let result = take_json(generate_test_card(99));
+        assert!(result.get("error").is_some());
+    }
+
+    #[test]
+    fn test_card_brand_classifies_known_prefixes() {
+        let brand = |pan: &str| {
+            let pan = CString::new(pan).unwrap();
+            unsafe { card_brand(pan.as_ptr()) }
+        };
+
+        assert_eq!(brand("4111 1111 1111 1111"), 0);
+        assert_eq!(brand("5555555555554444"), 1);
+        assert_eq!(brand("2223000048400011"), 1);
+        assert_eq!(brand("378282246310005"), 2);
+        assert_eq!(brand("4389350000000000"), 3);
+        assert_eq!(brand("6062820000000000"), 4);
+        assert_eq!(brand("9999000011112222"), -1);
+        assert_eq!(unsafe { card_brand(std::ptr::null()) }, -1);
+    }
+
+    #[test]
+    fn test_luhn_valid() {
+        assert!(luhn_valid("4111111111111111"));
+        assert!(luhn_valid("378282246310005"));
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(!luhn_valid("4111-1111"));
+    }
+
+    #[test]
+    fn test_parse_track2_well_formed() {
+        let track = CString::new(";4111111111111111=25122011234567890?7").unwrap();
+        let result = take_json(unsafe { parse_track2(track.as_ptr()) });
+
+        assert_eq!(result["pan"], "411111******1111");
+        assert_eq!(result["expiry"], "2512");
+        assert_eq!(result["service_code"], "201");
+    }
+
+    #[test]
+    fn test_parse_track2_malformed() {
+        assert!(parse_track2_str("4111111111111111D2512201").is_err());
+        assert!(parse_track2_str("4111111111111112=2512201").is_err());
+        assert!(parse_track2_str("4111111111111111=2513201").is_err());
+        assert!(parse_track2_str("4111111111111111=25").is_err());
+
+        let result = take_json(unsafe { parse_track2(std::ptr::null()) });
+        assert!(result.get("error").is_some());
+    }
+}
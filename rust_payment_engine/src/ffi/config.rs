@@ -0,0 +1,89 @@
+//! Configuração das regras do fluxo de pagamento
+//!
+//! Todas retornam 0 em sucesso ou -1 se o valor for rejeitado (configuração
+//! anterior mantida).
+
+use crate::state_machine::config;
+use std::time::Duration;
+
+/// Define os limites (R$) do adquirente aceitos por SetAmount
+#[no_mangle]
+pub extern "C" fn configure_amount_limits(min: f64, max: f64) -> i32 {
+    match config::set_amount_limits(min, max) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Define o valor (R$) acima do qual cancelamentos exigem motivo
+#[no_mangle]
+pub extern "C" fn configure_cancel_reason_threshold(threshold: f64) -> i32 {
+    match config::set_cancel_reason_threshold(threshold) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Define a janela (ms) após ProcessPayment em que cancelar ainda é permitido
+#[no_mangle]
+pub extern "C" fn configure_cancel_grace_window(window_ms: u64) -> i32 {
+    match config::set_cancel_grace_window(Duration::from_millis(window_ms)) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Define o total (R$) acima do qual a aprovação exige confirmação do portador
+#[no_mangle]
+pub extern "C" fn configure_cardholder_confirmation_threshold(threshold: f64) -> i32 {
+    match config::set_cardholder_confirmation_threshold(threshold) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_amount_limits() {
+        assert_eq!(configure_amount_limits(100.0, 10.0), -1);
+        assert_eq!(configure_amount_limits(f64::NAN, 10.0), -1);
+        assert_eq!(config::amount_limits(), (config::DEFAULT_MIN_AMOUNT, config::DEFAULT_MAX_AMOUNT));
+
+        assert_eq!(configure_amount_limits(1.0, 10_000.0), 0);
+        assert_eq!(config::amount_limits(), (1.0, 10_000.0));
+    }
+
+    #[test]
+    fn test_configure_cancel_reason_threshold() {
+        assert_eq!(configure_cancel_reason_threshold(f64::INFINITY), -1);
+        assert_eq!(configure_cancel_reason_threshold(-0.5), -1);
+        assert_eq!(config::cancel_reason_threshold(), config::DEFAULT_CANCEL_REASON_THRESHOLD);
+
+        assert_eq!(configure_cancel_reason_threshold(250.0), 0);
+        assert_eq!(config::cancel_reason_threshold(), 250.0);
+    }
+
+    #[test]
+    fn test_configure_cardholder_confirmation_threshold() {
+        assert_eq!(configure_cardholder_confirmation_threshold(f64::NAN), -1);
+        assert_eq!(
+            config::cardholder_confirmation_threshold(),
+            config::DEFAULT_CARDHOLDER_CONFIRMATION_THRESHOLD
+        );
+
+        assert_eq!(configure_cardholder_confirmation_threshold(0.0), 0);
+        assert_eq!(config::cardholder_confirmation_threshold(), 0.0);
+    }
+
+    #[test]
+    fn test_configure_cancel_grace_window() {
+        assert_eq!(configure_cancel_grace_window(30_001), -1);
+        assert_eq!(config::cancel_grace_window(), config::DEFAULT_CANCEL_GRACE_WINDOW);
+
+        assert_eq!(configure_cancel_grace_window(500), 0);
+        assert_eq!(config::cancel_grace_window(), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,125 @@
+//! Conversão dinâmica de moeda (DCC) para portadores estrangeiros
+
+use anyhow::Result;
+use serde::Serialize;
+use std::os::raw::c_char;
+
+use super::{json_result, read_c_str};
+use crate::money::{from_cents, to_cents, Amount};
+
+/// Oferta de DCC apresentada ao portador
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct DccOffer {
+    amount_local: f64,
+    local_currency: String,
+    /// Valor total na moeda do cartão (já com a margem)
+    converted_amount: f64,
+    card_currency: String,
+    /// Margem cobrada, divulgada separadamente na moeda do cartão
+    markup_amount: f64,
+    /// Margem percentual (0.03 = 3%)
+    markup: f64,
+    /// Taxa efetiva (câmbio com a margem): moeda do cartão por unidade local
+    effective_rate: f64,
+}
+
+/// Valida um código de moeda ISO 4217 (3 letras maiúsculas)
+fn parse_currency(code: Option<&str>) -> Result<String> {
+    let code = code.ok_or_else(|| anyhow::anyhow!("Moeda não informada"))?;
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(anyhow::anyhow!("Moeda inválida: {:?}", code));
+    }
+    Ok(code.to_string())
+}
+
+fn compute_dcc_offer(
+    amount_local: f64,
+    local_currency: Option<&str>,
+    card_currency: Option<&str>,
+    fx_rate: f64,
+    markup: f64,
+) -> Result<DccOffer> {
+    let amount = Amount::new(amount_local)?;
+    if amount.is_zero() {
+        return Err(anyhow::anyhow!("Valor deve ser maior que zero"));
+    }
+    let local_currency = parse_currency(local_currency)?;
+    let card_currency = parse_currency(card_currency)?;
+    if local_currency == card_currency {
+        return Err(anyhow::anyhow!("DCC exige moedas diferentes"));
+    }
+    if !fx_rate.is_finite() || fx_rate <= 0.0 {
+        return Err(anyhow::anyhow!("Taxa de câmbio deve ser positiva"));
+    }
+    let markup = Amount::new(markup).map_err(|_| anyhow::anyhow!("Margem inválida"))?;
+
+    // Base e margem arredondadas separadamente para que somem exatamente o total
+    let base_cents = to_cents(amount.value() * fx_rate);
+    let markup_cents = to_cents(from_cents(base_cents) * markup.value());
+
+    Ok(DccOffer {
+        amount_local: amount.value(),
+        local_currency,
+        converted_amount: from_cents(base_cents + markup_cents),
+        card_currency,
+        markup_amount: from_cents(markup_cents),
+        markup: markup.value(),
+        effective_rate: fx_rate * (1.0 + markup.value()),
+    })
+}
+
+/// Calcula a oferta de DCC para um valor em moeda local
+///
+/// `fx_rate` é a quantidade da moeda do cartão por unidade local e `markup`
+/// a margem como fração (0.03 = 3%). Retorna `{ amount_local, local_currency,
+/// converted_amount, card_currency, markup_amount, markup, effective_rate }`
+/// ou `{"error": ...}`.
+///
+/// # Safety
+/// `local_currency` e `card_currency` devem ser nulos ou strings C válidas.
+/// O retorno deve ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn dcc_offer(
+    amount_local: f64,
+    local_currency: *const c_char,
+    card_currency: *const c_char,
+    fx_rate: f64,
+    markup: f64,
+) -> *mut c_char {
+    json_result(compute_dcc_offer(
+        amount_local,
+        read_c_str(local_currency),
+        read_c_str(card_currency),
+        fx_rate,
+        markup,
+    ))
+}
+
+#[cfg(test)]
+mod dcc_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_dcc_offer_discloses_markup() {
+        let brl = CString::new("BRL").unwrap();
+        let usd = CString::new("USD").unwrap();
+        // R$ 100 a 0.20 USD/BRL = US$ 20.00; margem de 3% = US$ 0.60
+        let result = take_json(unsafe { dcc_offer(100.0, brl.as_ptr(), usd.as_ptr(), 0.20, 0.03) });
+
+        assert_eq!(result["converted_amount"].as_f64().unwrap(), 20.60);
+        assert_eq!(result["markup_amount"].as_f64().unwrap(), 0.60);
+        assert_eq!(result["card_currency"], "USD");
+        assert!((result["effective_rate"].as_f64().unwrap() - 0.206).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_dcc_offer_rejects_invalid_input() {
+        assert!(compute_dcc_offer(100.0, Some("BRL"), Some("usd"), 0.2, 0.03).is_err());
+        assert!(compute_dcc_offer(100.0, Some("BRL"), Some("BRL"), 0.2, 0.03).is_err());
+        assert!(compute_dcc_offer(100.0, Some("BRL"), Some("USD"), 0.0, 0.03).is_err());
+        assert!(compute_dcc_offer(100.0, Some("BRL"), None, 0.2, 0.03).is_err());
+        assert!(compute_dcc_offer(0.0, Some("BRL"), Some("USD"), 0.2, 0.03).is_err());
+    }
+}
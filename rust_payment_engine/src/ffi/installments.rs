@@ -0,0 +1,205 @@
+//! Cálculos de parcelamento
+
+use anyhow::Result;
+use serde::Serialize;
+use std::os::raw::c_char;
+
+use super::json_result;
+use crate::money::{from_cents, to_cents, Amount};
+
+/// Encargos de uma parcela em atraso
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct OverdueCharge {
+    /// Multa única (percentual sobre a parcela)
+    penalty: f64,
+    /// Juros simples acumulados pelos dias de atraso
+    interest: f64,
+    /// Valor total devido (parcela + multa + juros)
+    total: f64,
+}
+
+/// Calcula multa e juros de mora de uma parcela
+///
+/// Taxas são frações (0.02 = 2%). Sem atraso não há encargos. Cada
+/// componente é arredondado para centavos antes da soma.
+fn compute_overdue_charge(
+    installment_value: f64,
+    days_late: i32,
+    daily_interest: f64,
+    penalty_rate: f64,
+) -> Result<OverdueCharge> {
+    let installment = Amount::new(installment_value)?;
+    if days_late < 0 {
+        return Err(anyhow::anyhow!("Dias de atraso não podem ser negativos"));
+    }
+    let daily_interest = Amount::new(daily_interest)
+        .map_err(|_| anyhow::anyhow!("Juros diário inválido"))?;
+    let penalty_rate = Amount::new(penalty_rate)
+        .map_err(|_| anyhow::anyhow!("Multa inválida"))?;
+
+    let (penalty_cents, interest_cents) = if days_late == 0 {
+        (0, 0)
+    } else {
+        (
+            to_cents(installment.value() * penalty_rate.value()),
+            to_cents(installment.value() * daily_interest.value() * days_late as f64),
+        )
+    };
+
+    Ok(OverdueCharge {
+        penalty: from_cents(penalty_cents),
+        interest: from_cents(interest_cents),
+        total: from_cents(installment.cents() + penalty_cents + interest_cents),
+    })
+}
+
+/// Calcula os encargos de uma parcela em atraso
+///
+/// Retorna `{ "penalty", "interest", "total" }` ou `{"error": ...}` para
+/// entradas negativas ou não finitas.
+///
+/// # Safety
+/// O retorno deve ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn overdue_charge(
+    installment_value: f64,
+    days_late: i32,
+    daily_interest: f64,
+    penalty_rate: f64,
+) -> *mut c_char {
+    json_result(compute_overdue_charge(installment_value, days_late, daily_interest, penalty_rate))
+}
+
+/// Número máximo de parcelas aceito
+const MAX_INSTALLMENTS: i32 = 120;
+
+/// Plano de parcelamento
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct InstallmentPlan {
+    /// Valor de cada parcela, na ordem de vencimento
+    installments: Vec<f64>,
+    /// Total pago com juros (soma exata das parcelas)
+    total: f64,
+}
+
+/// Divide `amount` em `n` parcelas pela Tabela Price (juros compostos mensais)
+///
+/// A parcela fixa é `amount * i / (1 - (1 + i)^-n)`; sem juros o valor é
+/// apenas dividido. O total é arredondado para centavos e os centavos que
+/// não dividem igualmente vão para a primeira parcela.
+fn compute_installments(amount: f64, n: i32, monthly_rate: f64) -> Result<InstallmentPlan> {
+    let amount = Amount::new(amount)?;
+    if amount.is_zero() {
+        return Err(anyhow::anyhow!("Valor deve ser maior que zero"));
+    }
+    if !(1..=MAX_INSTALLMENTS).contains(&n) {
+        return Err(anyhow::anyhow!("Número de parcelas deve estar entre 1 e {}", MAX_INSTALLMENTS));
+    }
+    let rate = Amount::new(monthly_rate)
+        .map_err(|_| anyhow::anyhow!("Taxa de juros inválida"))?
+        .value();
+
+    let total_cents = if rate == 0.0 {
+        amount.cents()
+    } else {
+        let payment = amount.value() * rate / (1.0 - (1.0 + rate).powi(-n));
+        to_cents(payment) * i64::from(n)
+    };
+
+    let count = i64::from(n);
+    let base = total_cents / count;
+    let remainder = total_cents % count;
+    let installments = (0..count)
+        .map(|idx| from_cents(if idx == 0 { base + remainder } else { base }))
+        .collect();
+
+    Ok(InstallmentPlan {
+        installments,
+        total: from_cents(total_cents),
+    })
+}
+
+/// Calcula o parcelamento de uma venda no crédito
+///
+/// `monthly_rate` é uma fração (0.0199 = 1.99% a.m.); `n` vai de 1 a 120.
+/// Retorna `{ "installments": [...], "total" }` ou `{"error": ...}`.
+///
+/// # Safety
+/// O retorno deve ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_installments(amount: f64, n: i32, monthly_rate: f64) -> *mut c_char {
+    json_result(compute_installments(amount, n, monthly_rate))
+}
+
+#[cfg(test)]
+mod installments_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+
+    #[test]
+    fn test_overdue_charge_ten_days_late() {
+        // 100.00 com multa de 2% e 0.033% ao dia por 10 dias:
+        // multa 2.00, juros 100 * 0.00033 * 10 = 0.33, total 102.33
+        let result = take_json(unsafe { overdue_charge(100.0, 10, 0.00033, 0.02) });
+
+        assert_eq!(result["penalty"].as_f64().unwrap(), 2.0);
+        assert_eq!(result["interest"].as_f64().unwrap(), 0.33);
+        assert_eq!(result["total"].as_f64().unwrap(), 102.33);
+    }
+
+    #[test]
+    fn test_overdue_charge_on_time_has_no_charges() {
+        let charge = compute_overdue_charge(250.0, 0, 0.001, 0.02).unwrap();
+        assert_eq!(charge, OverdueCharge { penalty: 0.0, interest: 0.0, total: 250.0 });
+    }
+
+    #[test]
+    fn test_overdue_charge_rejects_negative_inputs() {
+        assert!(compute_overdue_charge(-1.0, 10, 0.001, 0.02).is_err());
+        assert!(compute_overdue_charge(100.0, -1, 0.001, 0.02).is_err());
+        assert!(compute_overdue_charge(100.0, 10, -0.001, 0.02).is_err());
+        assert!(compute_overdue_charge(100.0, 10, 0.001, f64::NAN).is_err());
+    }
+
+    fn sum_cents(plan: &serde_json::Value) -> i64 {
+        plan["installments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| to_cents(v.as_f64().unwrap()))
+            .sum()
+    }
+
+    #[test]
+    fn test_installments_price_table_sum_matches_total() {
+        // 1000.00 em 12x a 1.99% a.m.: parcela de 94.50, total 1134.00
+        let plan = take_json(unsafe { calculate_installments(1000.0, 12, 0.0199) });
+
+        let installments = plan["installments"].as_array().unwrap();
+        assert_eq!(installments.len(), 12);
+        assert_eq!(installments[11].as_f64().unwrap(), 94.5);
+        assert_eq!(plan["total"].as_f64().unwrap(), 1134.0);
+        assert_eq!(sum_cents(&plan), 113_400);
+    }
+
+    #[test]
+    fn test_installments_without_interest_split_evenly() {
+        // 100.00 em 3x: 33.34 + 33.33 + 33.33
+        let plan = take_json(unsafe { calculate_installments(100.0, 3, 0.0) });
+
+        assert_eq!(plan["installments"], serde_json::json!([33.34, 33.33, 33.33]));
+        assert_eq!(plan["total"].as_f64().unwrap(), 100.0);
+        assert_eq!(sum_cents(&plan), 10_000);
+    }
+
+    #[test]
+    fn test_installments_rejects_invalid_input() {
+        assert!(compute_installments(100.0, 0, 0.01).is_err());
+        assert!(compute_installments(100.0, 3, -0.01).is_err());
+        assert!(compute_installments(0.0, 3, 0.01).is_err());
+        assert!(compute_installments(100.0, MAX_INSTALLMENTS + 1, 0.01).is_err());
+
+        let result = take_json(unsafe { calculate_installments(f64::NAN, 3, 0.01) });
+        assert!(result.get("error").is_some());
+    }
+}
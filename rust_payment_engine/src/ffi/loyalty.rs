@@ -0,0 +1,128 @@
+//! Números de fidelidade protegidos por Luhn
+//!
+//! Usam um prefixo próprio do programa, distinto dos BINs de cartão, para
+//! que um número de fidelidade nunca seja confundido com um cartão de
+//! pagamento.
+
+use anyhow::Result;
+use std::os::raw::c_char;
+
+use super::card::{luhn_number, luhn_valid};
+use super::{into_c_string, json_result, read_c_str};
+
+/// Comprimento mínimo de um número de fidelidade (com dígito verificador)
+const MIN_LENGTH: usize = 8;
+
+/// Comprimento máximo de um número de fidelidade
+const MAX_LENGTH: usize = 19;
+
+fn is_digits(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Gera um número `prefix` + dígitos aleatórios + dígito verificador
+fn generate(prefix: &str, length: i32) -> Result<String> {
+    if !is_digits(prefix) {
+        return Err(anyhow::anyhow!("Prefixo deve conter apenas dígitos"));
+    }
+    let length = usize::try_from(length).unwrap_or(0);
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(anyhow::anyhow!(
+            "Comprimento deve estar entre {} e {}",
+            MIN_LENGTH,
+            MAX_LENGTH
+        ));
+    }
+    if prefix.len() >= length {
+        return Err(anyhow::anyhow!("Prefixo não deixa espaço para o número"));
+    }
+
+    Ok(luhn_number(prefix, length))
+}
+
+/// Indica se `number` tem o prefixo esperado, comprimento válido e passa no Luhn
+fn is_valid(number: &str, expected_prefix: &str) -> bool {
+    is_digits(expected_prefix)
+        && is_digits(number)
+        && (MIN_LENGTH..=MAX_LENGTH).contains(&number.len())
+        && number.starts_with(expected_prefix)
+        && luhn_valid(number)
+}
+
+/// Gera um número de fidelidade com o prefixo do programa
+///
+/// `length` inclui o prefixo e o dígito verificador (8 a 19). Retorna o
+/// número (apenas dígitos) ou `{"error": ...}`.
+///
+/// # Safety
+/// `prefix` deve ser nulo ou uma string C válida. O retorno deve ser
+/// liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn generate_loyalty_number(prefix: *const c_char, length: i32) -> *mut c_char {
+    match read_c_str(prefix).ok_or_else(|| anyhow::anyhow!("Prefixo não informado")) {
+        Ok(prefix) => match generate(prefix, length) {
+            Ok(number) => into_c_string(number),
+            Err(e) => json_result::<()>(Err(e)),
+        },
+        Err(e) => json_result::<()>(Err(e)),
+    }
+}
+
+/// Valida um número de fidelidade: 1 se válido, 0 caso contrário
+///
+/// Exige o prefixo esperado e dígito verificador de Luhn correto.
+///
+/// # Safety
+/// `number` e `expected_prefix` devem ser nulos ou strings C válidas.
+#[no_mangle]
+pub unsafe extern "C" fn validate_loyalty_number(
+    number: *const c_char,
+    expected_prefix: *const c_char,
+) -> i32 {
+    match (read_c_str(number), read_c_str(expected_prefix)) {
+        (Some(number), Some(prefix)) => i32::from(is_valid(number, prefix)),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod loyalty_tests {
+    use super::*;
+    use crate::ffi::test_support::take_string;
+    use std::ffi::CString;
+
+    fn validate(number: &str, prefix: &str) -> i32 {
+        let number = CString::new(number).unwrap();
+        let prefix = CString::new(prefix).unwrap();
+        unsafe { validate_loyalty_number(number.as_ptr(), prefix.as_ptr()) }
+    }
+
+    #[test]
+    fn test_generate_and_validate_round_trip() {
+        let prefix = CString::new("9870").unwrap();
+        for _ in 0..20 {
+            let number = take_string(unsafe { generate_loyalty_number(prefix.as_ptr(), 16) });
+
+            assert_eq!(number.len(), 16);
+            assert!(number.starts_with("9870"));
+            assert_eq!(validate(&number, "9870"), 1);
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_prefix_and_check_digit() {
+        let number = generate("9870", 12).unwrap();
+        assert_eq!(validate(&number, "9871"), 0);
+
+        let last = number.chars().last().unwrap().to_digit(10).unwrap();
+        let tampered = format!("{}{}", &number[..11], (last + 1) % 10);
+        assert_eq!(validate(&tampered, "9870"), 0);
+    }
+
+    #[test]
+    fn test_generate_rejects_invalid_parameters() {
+        assert!(generate("98A", 16).is_err());
+        assert!(generate("9870", 4).is_err());
+        assert!(generate("98701234", 8).is_err());
+    }
+}
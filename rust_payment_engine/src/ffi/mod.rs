@@ -0,0 +1,86 @@
+//! Funções auxiliares expostas via C ABI
+//!
+//! Diferente da API da máquina de estados (exposta pelo flutter_rust_bridge),
+//! estas funções são chamadas diretamente via `dart:ffi`.
+//!
+//! **Contrato de memória:** toda string retornada (`*mut c_char`) é alocada
+//! pelo Rust e DEVE ser liberada com `free_rust_string`.
+
+pub mod bin_table;
+pub mod card;
+pub mod config;
+pub mod dcc;
+pub mod installments;
+pub mod loyalty;
+pub mod pix;
+pub mod replay;
+pub mod schema;
+pub mod settlement;
+pub mod snapshot;
+pub mod stan;
+pub mod state_machine;
+pub mod stats;
+pub mod timestamp;
+pub mod tip;
+pub mod transaction_id;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// Libera uma string alocada pelo Rust
+///
+/// # Safety
+/// `ptr` deve ter sido retornado por uma função deste módulo e não pode
+/// ser liberado duas vezes. Ponteiro nulo é ignorado.
+#[no_mangle]
+pub unsafe extern "C" fn free_rust_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Converte uma string Rust em ponteiro C (dono: chamador)
+pub(crate) fn into_c_string(value: String) -> *mut c_char {
+    CString::new(value)
+        .unwrap_or_else(|_| CString::new("{\"error\":\"string inválida\"}").unwrap())
+        .into_raw()
+}
+
+/// Lê uma string C como `&str` (None para ponteiro nulo ou UTF-8 inválido)
+///
+/// # Safety
+/// `ptr` deve ser nulo ou apontar para uma string C terminada em zero.
+pub(crate) unsafe fn read_c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+/// Serializa um valor como JSON, ou `{"error": ...}` em caso de falha
+pub(crate) fn json_result<T: serde::Serialize>(result: anyhow::Result<T>) -> *mut c_char {
+    let json = match result {
+        Ok(value) => serde_json::to_string(&value)
+            .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }).to_string()),
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    };
+    into_c_string(json)
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    /// Consome uma string retornada via FFI, liberando-a
+    pub fn take_string(ptr: *mut c_char) -> String {
+        assert!(!ptr.is_null());
+        let value = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { free_rust_string(ptr) };
+        value
+    }
+
+    /// Consome uma string JSON retornada via FFI
+    pub fn take_json(ptr: *mut c_char) -> serde_json::Value {
+        serde_json::from_str(&take_string(ptr)).unwrap()
+    }
+}
@@ -0,0 +1,50 @@
+//! Configuração do recebedor PIX
+
+use std::os::raw::c_char;
+
+use super::read_c_str;
+use crate::state_machine::config::set_pix_merchant;
+
+/// Define chave, nome e cidade do recebedor usados no BR Code
+///
+/// Retorna 0 em sucesso ou -1 se algum campo for nulo, vazio, não ASCII
+/// ou exceder o limite do BR Code (configuração anterior mantida).
+///
+/// # Safety
+/// `key`, `name` e `city` devem ser nulos ou strings C válidas.
+#[no_mangle]
+pub unsafe extern "C" fn configure_pix_merchant(
+    key: *const c_char,
+    name: *const c_char,
+    city: *const c_char,
+) -> i32 {
+    let (Some(key), Some(name), Some(city)) = (read_c_str(key), read_c_str(name), read_c_str(city)) else {
+        return -1;
+    };
+    match set_pix_merchant(key, name, city) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+#[cfg(test)]
+mod pix_tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_configure_pix_merchant_rejects_invalid_fields() {
+        let key = CString::new("loja@example.com").unwrap();
+        let name = CString::new("LOJA EXEMPLO").unwrap();
+        let long_city = CString::new("CIDADE COM NOME LONGO").unwrap();
+        let accented = CString::new("SÃO PAULO").unwrap();
+        let blank = CString::new(" ").unwrap();
+
+        unsafe {
+            assert_eq!(configure_pix_merchant(key.as_ptr(), name.as_ptr(), long_city.as_ptr()), -1);
+            assert_eq!(configure_pix_merchant(key.as_ptr(), name.as_ptr(), accented.as_ptr()), -1);
+            assert_eq!(configure_pix_merchant(blank.as_ptr(), name.as_ptr(), name.as_ptr()), -1);
+            assert_eq!(configure_pix_merchant(key.as_ptr(), std::ptr::null(), name.as_ptr()), -1);
+        }
+    }
+}
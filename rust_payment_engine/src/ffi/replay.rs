@@ -0,0 +1,125 @@
+//! Replay de sessões gravadas a partir de uma sequência de comandos
+
+use anyhow::Result;
+use serde::Serialize;
+use std::os::raw::c_char;
+
+use super::{json_result, read_c_str};
+use crate::state_machine::{ActionOutcome, PaymentStateApi, StateAction};
+
+/// Resultado de um comando do replay
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ReplayStep {
+    Applied(ActionOutcome),
+    Failed { index: usize, error: String },
+}
+
+/// Aplica os comandos em ordem a uma sessão nova, parando no primeiro erro
+fn replay(commands_json: &str) -> Result<Vec<ReplayStep>> {
+    let commands: Vec<serde_json::Value> = serde_json::from_str(commands_json)?;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+    runtime.block_on(async {
+        let api = PaymentStateApi::new();
+        let mut steps = Vec::with_capacity(commands.len());
+
+        for (index, command) in commands.into_iter().enumerate() {
+            let result = match serde_json::from_value::<StateAction>(command) {
+                Ok(action) => api.execute_action(action).await,
+                Err(e) => Err(anyhow::anyhow!("Ação JSON inválida: {}", e)),
+            };
+            match result {
+                Ok(outcome) => steps.push(ReplayStep::Applied(outcome)),
+                Err(e) => {
+                    steps.push(ReplayStep::Failed { index, error: e.to_string() });
+                    break;
+                }
+            }
+        }
+        Ok(steps)
+    })
+}
+
+/// Reexecuta uma sessão gravada (array JSON de `StateAction`)
+///
+/// Cada comando é aplicado em ordem a uma máquina nova, em AwaitingInfo.
+/// Retorna um array com um `{ "from_state", "state", "transitioned", "message" }`
+/// por comando aplicado; no primeiro erro o replay para e o último item é
+/// `{ "index", "error" }`. Entrada que não é um array JSON resulta em
+/// `{"error": ...}`. Liberar com `free_rust_string`.
+///
+/// # Safety
+/// `commands_json` deve ser nulo ou uma string C terminada em zero. Não
+/// deve ser chamada de dentro de um runtime tokio.
+#[no_mangle]
+pub unsafe extern "C" fn replay_commands_json(commands_json: *const c_char) -> *mut c_char {
+    let commands = read_c_str(commands_json).ok_or_else(|| anyhow::anyhow!("Comandos não informados"));
+    json_result(commands.and_then(replay))
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+    use serde_json::json;
+    use std::ffi::CString;
+
+    fn run(commands: serde_json::Value) -> serde_json::Value {
+        let input = CString::new(commands.to_string()).unwrap();
+        take_json(unsafe { replay_commands_json(input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_replay_full_successful_flow() {
+        let result = run(json!([
+            { "type": "AwaitingInfo", "data": { "type": "SetAmount", "data": { "amount": 100.0 } } },
+            { "type": "AwaitingInfo", "data": { "type": "SetPaymentType", "data": { "payment_type": "Credit" } } },
+            { "type": "AwaitingInfo", "data": { "type": "ConfirmInfo" } },
+            { "type": "EmvPayment", "data": { "type": "ProcessPayment" } },
+            { "type": "EmvPayment", "data": { "type": "CompletePayment", "data": { "result": {
+                "transaction_id": "TXN-REPLAY",
+                "authorization_code": "AUTH",
+                "timestamp": "2024-01-01T12:00:00Z",
+            } } } },
+        ]));
+
+        let states: Vec<_> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| step["state"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(states, ["AwaitingInfo", "AwaitingInfo", "EMVPayment", "EMVPayment", "PaymentSuccess"]);
+        assert_eq!(result[2]["transitioned"], true);
+        assert_eq!(result[4]["from_state"], "EMVPayment");
+    }
+
+    #[test]
+    fn test_replay_stops_at_first_error() {
+        let result = run(json!([
+            { "type": "AwaitingInfo", "data": { "type": "SetAmount", "data": { "amount": 50.0 } } },
+            { "type": "AwaitingInfo", "data": { "type": "ConfirmInfo" } },
+            { "type": "AwaitingInfo", "data": { "type": "SetPaymentType", "data": { "payment_type": "Debit" } } },
+        ]));
+
+        let steps = result.as_array().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0]["state"], "AwaitingInfo");
+        assert_eq!(steps[1]["index"], 1);
+        assert!(steps[1]["error"].as_str().unwrap().contains("Tipo de pagamento não definido"));
+    }
+
+    #[test]
+    fn test_replay_rejects_invalid_input() {
+        let result = take_json(unsafe { replay_commands_json(std::ptr::null()) });
+        assert!(result.get("error").is_some());
+
+        let result = run(json!({ "type": "AwaitingInfo" }));
+        assert!(result.get("error").is_some());
+
+        let result = run(json!([{ "type": "Desconhecido" }]));
+        assert_eq!(result[0]["index"], 0);
+        assert!(result[0]["error"].as_str().unwrap().contains("Ação JSON inválida"));
+    }
+}
@@ -0,0 +1,59 @@
+//! Schema da máquina de estados para ferramentas e geração de bindings Dart
+
+use std::os::raw::c_char;
+
+use super::into_c_string;
+use crate::state_machine::schema::machine_schema_json;
+
+/// Descreve a máquina de estados como JSON
+///
+/// Array com um objeto por estado:
+/// `{ "state", "actions": [{ "name", "params": [{ "name", "type" }] }], "transitions" }`.
+/// Liberar com `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn export_machine_schema() -> *mut c_char {
+    into_c_string(machine_schema_json())
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+
+    fn action_names(schema: &serde_json::Value, state: &str) -> Vec<String> {
+        let entry = schema
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["state"] == state)
+            .unwrap_or_else(|| panic!("estado {} ausente", state));
+        entry["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_lists_states_and_actions() {
+        let schema = take_json(export_machine_schema());
+
+        assert_eq!(
+            action_names(&schema, "AwaitingInfo"),
+            ["SetAmount", "SetPaymentType", "SetTip", "SetRequireProcessing", "ConfirmInfo"]
+        );
+        assert!(action_names(&schema, "EMVPayment").contains(&"CompletePayment".to_string()));
+        assert_eq!(action_names(&schema, "PaymentSuccess"), ["Reset", "RefundPayment", "SupplementalCharge", "AdjustTip"]);
+    }
+
+    #[test]
+    fn test_schema_describes_params_and_transitions() {
+        let schema = take_json(export_machine_schema());
+        let awaiting = &schema[0];
+
+        assert_eq!(awaiting["actions"][0]["params"][0]["name"], "amount");
+        assert_eq!(awaiting["actions"][0]["params"][0]["type"], "f64");
+        assert_eq!(awaiting["transitions"], serde_json::json!(["EMVPayment", "PixPayment"]));
+    }
+}
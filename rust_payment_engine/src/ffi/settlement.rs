@@ -0,0 +1,322 @@
+//! Cálculos de liquidação e repasse
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::os::raw::c_char;
+
+use super::bin_table::payment_type_from_code;
+use super::{json_result, read_c_str};
+use crate::money::{amounts_equal, from_cents, to_cents};
+use crate::state_machine::PaymentType;
+
+/// Tolerância aceita na soma das participações de um split
+const SHARE_SUM_TOLERANCE: f64 = 1e-6;
+
+/// Maior janela de reserva aceita (um ano)
+const MAX_ROLLING_DAYS: i32 = 365;
+
+/// Participação de um recebedor no split
+#[derive(Debug, Clone, Deserialize)]
+struct SplitRecipient {
+    id: String,
+    share: f64,
+}
+
+/// Valor final de um recebedor no split
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct SplitAmount {
+    id: String,
+    amount: f64,
+}
+
+/// Divide `total` entre os recebedores
+///
+/// Cada recebedor recebe o piso de `total * share` em centavos; os centavos
+/// restantes vão para as maiores frações descartadas (empate: ordem de entrada),
+/// garantindo que a soma seja exatamente o total.
+fn compute_split(total: f64, recipients: &[SplitRecipient]) -> Result<Vec<SplitAmount>> {
+    if !total.is_finite() || total < 0.0 {
+        return Err(anyhow::anyhow!("Total inválido"));
+    }
+    if recipients.is_empty() {
+        return Err(anyhow::anyhow!("Nenhum recebedor informado"));
+    }
+    if let Some(r) = recipients.iter().find(|r| r.id.trim().is_empty()) {
+        return Err(anyhow::anyhow!("Recebedor sem id (participação {})", r.share));
+    }
+    if let Some(r) = recipients.iter().find(|r| !r.share.is_finite() || r.share < 0.0) {
+        return Err(anyhow::anyhow!("Participação inválida para {}", r.id));
+    }
+    let share_sum: f64 = recipients.iter().map(|r| r.share).sum();
+    if (share_sum - 1.0).abs() > SHARE_SUM_TOLERANCE {
+        return Err(anyhow::anyhow!(
+            "Participações devem somar 1.0 (soma: {})",
+            share_sum
+        ));
+    }
+
+    let total_cents = to_cents(total);
+    let mut cents: Vec<i64> = Vec::with_capacity(recipients.len());
+    let mut fractions: Vec<(usize, f64)> = Vec::with_capacity(recipients.len());
+    for (idx, r) in recipients.iter().enumerate() {
+        // Normaliza pela soma para que o piso nunca ultrapasse o total
+        let exact = total_cents as f64 * r.share / share_sum;
+        let floor = exact.floor();
+        cents.push(floor as i64);
+        fractions.push((idx, exact - floor));
+    }
+
+    // Maior fração primeiro; empate pela ordem de entrada
+    fractions.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let remainder = total_cents - cents.iter().sum::<i64>();
+    for &(idx, _) in fractions.iter().cycle().take(remainder.max(0) as usize) {
+        cents[idx] += 1;
+    }
+    debug_assert!(amounts_equal(from_cents(cents.iter().sum()), total, 0));
+
+    Ok(recipients
+        .iter()
+        .zip(cents)
+        .map(|(r, c)| SplitAmount {
+            id: r.id.clone(),
+            amount: from_cents(c),
+        })
+        .collect())
+}
+
+/// Divide um pagamento entre múltiplos recebedores (marketplace)
+///
+/// `recipients_json` é um array `[{ "id": "...", "share": 0.5 }, ...]` cujas
+/// participações devem somar 1.0. Retorna `[{ "id", "amount" }, ...]` com a
+/// soma exatamente igual a `total`, ou `{"error": ...}`.
+///
+/// # Safety
+/// `recipients_json` deve ser nulo ou uma string C válida. O retorno deve
+/// ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn split_settlement(total: f64, recipients_json: *const c_char) -> *mut c_char {
+    json_result(split_from_json(total, read_c_str(recipients_json)))
+}
+
+fn split_from_json(total: f64, json: Option<&str>) -> Result<Vec<SplitAmount>> {
+    let json = json.ok_or_else(|| anyhow::anyhow!("JSON não informado"))?;
+    let recipients: Vec<SplitRecipient> = serde_json::from_str(json)?;
+    compute_split(total, &recipients)
+}
+
+/// Liberação de uma parcela da reserva
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct ReserveRelease {
+    /// Dias a partir de hoje até a liberação (1..=rolling_days)
+    day: i32,
+    amount: f64,
+}
+
+/// Reserva retida e seu cronograma de liberação
+#[derive(Debug, Clone, Serialize)]
+struct ReserveSchedule {
+    held: f64,
+    releases: Vec<ReserveRelease>,
+}
+
+/// Calcula a reserva `volume * reserve_rate` e a divide em liberações diárias
+///
+/// O volume é tratado como distribuído igualmente pela janela, então cada
+/// dia libera a mesma parcela; os centavos restantes vão para os primeiros
+/// dias, garantindo que a soma das liberações seja exatamente o valor retido.
+fn compute_reserve_schedule(volume: f64, reserve_rate: f64, rolling_days: i32) -> Result<ReserveSchedule> {
+    if !volume.is_finite() || volume < 0.0 {
+        return Err(anyhow::anyhow!("Volume inválido"));
+    }
+    if !(0.0..=1.0).contains(&reserve_rate) {
+        return Err(anyhow::anyhow!("Taxa de reserva deve estar entre 0 e 1"));
+    }
+    if rolling_days < 1 {
+        return Err(anyhow::anyhow!("Janela da reserva deve ter ao menos 1 dia"));
+    }
+    if rolling_days > MAX_ROLLING_DAYS {
+        return Err(anyhow::anyhow!(
+            "Janela da reserva não pode exceder {} dias",
+            MAX_ROLLING_DAYS
+        ));
+    }
+
+    let held_cents = to_cents(volume * reserve_rate);
+    let days = i64::from(rolling_days);
+    let releases = (1..=rolling_days)
+        .map(|day| {
+            let extra = i64::from(i64::from(day) <= held_cents % days);
+            ReserveRelease {
+                day,
+                amount: from_cents(held_cents / days + extra),
+            }
+        })
+        .collect();
+
+    Ok(ReserveSchedule {
+        held: from_cents(held_cents),
+        releases,
+    })
+}
+
+/// Reserva (rolling hold) retida pelo adquirente contra chargebacks
+///
+/// `reserve_rate` deve estar em [0, 1] e `rolling_days` entre 1 e 365.
+/// Retorna `{ "held", "releases": [{ "day", "amount" }, ...] }` com
+/// `rolling_days` liberações, ou `{"error": ...}`. Liberar com
+/// `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn compute_reserve(volume: f64, reserve_rate: f64, rolling_days: i32) -> *mut c_char {
+    json_result(compute_reserve_schedule(volume, reserve_rate, rolling_days))
+}
+
+/// Prazo de liquidação (D+N) de cada tipo de pagamento
+fn settlement_days(payment_type: &PaymentType) -> f64 {
+    match payment_type {
+        PaymentType::Debit => 1.0,
+        PaymentType::Credit => 30.0,
+        // PIX cai na hora; verificação não movimenta valor
+        PaymentType::Pix | PaymentType::Verification => 0.0,
+    }
+}
+
+/// Média dos prazos de liquidação ponderada pelo valor de cada venda
+fn weighted_settlement_days(methods: &[i32], amounts: &[f64]) -> Result<f64> {
+    let mut volume = 0.0;
+    let mut weighted = 0.0;
+    for (idx, (&method, &amount)) in methods.iter().zip(amounts).enumerate() {
+        if !amount.is_finite() || amount < 0.0 {
+            return Err(anyhow::anyhow!("Valor inválido no índice {}", idx));
+        }
+        let payment_type = payment_type_from_code(method)?;
+        volume += amount;
+        weighted += amount * settlement_days(&payment_type);
+    }
+    if volume == 0.0 {
+        return Err(anyhow::anyhow!("Volume total zero"));
+    }
+    Ok(weighted / volume)
+}
+
+/// Prazo médio até o dinheiro cair ("dias até o dinheiro") de um lote
+///
+/// `methods[i]` segue a ordem de `PaymentType` (0=Debit, 1=Credit,
+/// 2=Verification, 3=Pix) com prazos D+1 (débito), D+30 (crédito) e D+0.
+/// Retorna a média ponderada pelos valores, ou NaN para volume total zero,
+/// ponteiro nulo, tipo desconhecido ou valor inválido.
+///
+/// # Safety
+/// `methods` e `amounts` devem apontar para `count` elementos cada (ou ser
+/// nulos com `count == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn average_settlement_days(methods: *const i32, amounts: *const f64, count: usize) -> f64 {
+    if count == 0 || methods.is_null() || amounts.is_null() {
+        return f64::NAN;
+    }
+    let methods = std::slice::from_raw_parts(methods, count);
+    let amounts = std::slice::from_raw_parts(amounts, count);
+    weighted_settlement_days(methods, amounts).unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod settlement_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+    use std::ffi::CString;
+
+    fn split(total: f64, json: &str) -> serde_json::Value {
+        let input = CString::new(json).unwrap();
+        take_json(unsafe { split_settlement(total, input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_split_three_way_assigns_remainder_deterministically() {
+        let result = split(
+            100.0,
+            r#"[{"id":"a","share":0.3333333333333333},{"id":"b","share":0.3333333333333333},{"id":"c","share":0.3333333333333333}]"#,
+        );
+        let amounts: Vec<f64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["amount"].as_f64().unwrap())
+            .collect();
+
+        assert_eq!(amounts, vec![33.34, 33.33, 33.33]);
+        let sum_cents: i64 = amounts.iter().map(|a| to_cents(*a)).sum();
+        assert_eq!(sum_cents, 10_000);
+    }
+
+    #[test]
+    fn test_split_rejects_shares_not_summing_to_one() {
+        let result = split(100.0, r#"[{"id":"a","share":0.5},{"id":"b","share":0.4}]"#);
+        assert!(result["error"].as_str().unwrap().contains("somar 1.0"));
+    }
+
+    #[test]
+    fn test_split_rejects_null_and_malformed_input() {
+        let result = take_json(unsafe { split_settlement(10.0, std::ptr::null()) });
+        assert!(result.get("error").is_some());
+
+        let result = split(10.0, "not json");
+        assert!(result.get("error").is_some());
+    }
+
+    #[test]
+    fn test_reserve_holds_volume_times_rate_over_window() {
+        let result = take_json(compute_reserve(10_000.0, 0.1, 30));
+        assert_eq!(result["held"].as_f64().unwrap(), 1_000.0);
+
+        let releases = result["releases"].as_array().unwrap();
+        assert_eq!(releases.len(), 30);
+        assert_eq!(releases[0]["day"], 1);
+        assert_eq!(releases[29]["day"], 30);
+        let released: i64 = releases.iter().map(|r| to_cents(r["amount"].as_f64().unwrap())).sum();
+        assert_eq!(released, 100_000);
+        // 100000 / 30 = 3333 centavos, resto 10 para os primeiros dias
+        assert_eq!(releases[0]["amount"].as_f64().unwrap(), 33.34);
+        assert_eq!(releases[10]["amount"].as_f64().unwrap(), 33.33);
+    }
+
+    #[test]
+    fn test_reserve_rejects_invalid_inputs() {
+        for (volume, rate, days) in [(100.0, 1.5, 10), (100.0, -0.1, 10), (100.0, f64::NAN, 10), (-1.0, 0.1, 10), (100.0, 0.1, 0), (100.0, 0.1, MAX_ROLLING_DAYS + 1)] {
+            let result = take_json(compute_reserve(volume, rate, days));
+            assert!(result.get("error").is_some(), "{} {} {}", volume, rate, days);
+        }
+    }
+
+    #[test]
+    fn test_reserve_accepts_one_year_window() {
+        let result = take_json(compute_reserve(365.0, 1.0, MAX_ROLLING_DAYS));
+        let releases = result["releases"].as_array().unwrap();
+
+        assert_eq!(releases.len(), 365);
+        assert_eq!(releases[364]["amount"].as_f64().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_average_settlement_days_weights_by_amount() {
+        // 300 no débito (D+1) e 100 no crédito (D+30): (300 + 3000) / 400
+        let methods = [0, 1, 0];
+        let amounts = [200.0, 100.0, 100.0];
+        let days = unsafe { average_settlement_days(methods.as_ptr(), amounts.as_ptr(), 3) };
+
+        assert!((days - 8.25).abs() < 1e-9);
+        assert!(days > 1.0 && days < 30.0);
+    }
+
+    #[test]
+    fn test_average_settlement_days_invalid_input_is_nan() {
+        let methods = [0, 2];
+        let zero = [0.0, 0.0];
+        assert!(unsafe { average_settlement_days(methods.as_ptr(), zero.as_ptr(), 2) }.is_nan());
+        assert!(unsafe { average_settlement_days(std::ptr::null(), std::ptr::null(), 0) }.is_nan());
+        assert!(unsafe { average_settlement_days(methods.as_ptr(), std::ptr::null(), 2) }.is_nan());
+
+        let unknown = [0, 9];
+        let amounts = [10.0, 10.0];
+        assert!(unsafe { average_settlement_days(unknown.as_ptr(), amounts.as_ptr(), 2) }.is_nan());
+        assert!(weighted_settlement_days(&[1], &[-1.0]).is_err());
+    }
+}
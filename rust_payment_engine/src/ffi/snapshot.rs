@@ -0,0 +1,53 @@
+//! Comparação de snapshots do fluxo para diagnóstico pelo suporte
+
+use std::os::raw::c_char;
+
+use super::{json_result, read_c_str};
+use crate::state_machine::snapshot_diff::diff_snapshot_json;
+
+/// Compara dois snapshots (JSON de `snapshot()`) campo a campo
+///
+/// Retorna um array JSON `[{ "path", "before", "after" }]`, com `null`
+/// em `before`/`after` para campos adicionados/removidos, ou `{"error": ...}`.
+///
+/// # Safety
+/// `a` e `b` devem ser nulos ou strings C terminadas em zero. O retorno
+/// deve ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn diff_state_snapshots(a: *const c_char, b: *const c_char) -> *mut c_char {
+    let snapshots = read_c_str(a)
+        .zip(read_c_str(b))
+        .ok_or_else(|| anyhow::anyhow!("Snapshot não informado"));
+    json_result(snapshots.and_then(|(a, b)| diff_snapshot_json(a, b)))
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_diff_state_snapshots_reports_changes() {
+        let a = CString::new(r#"{"state_type":"AwaitingInfo","state":{"amount":10.0,"payment_type":null}}"#).unwrap();
+        let b = CString::new(r#"{"state_type":"AwaitingInfo","state":{"amount":25.0,"payment_type":"Credit"}}"#).unwrap();
+
+        let result = take_json(unsafe { diff_state_snapshots(a.as_ptr(), b.as_ptr()) });
+
+        assert_eq!(result, serde_json::json!([
+            { "path": "amount", "before": 10.0, "after": 25.0 },
+            { "path": "payment_type", "before": null, "after": "Credit" },
+        ]));
+    }
+
+    #[test]
+    fn test_diff_state_snapshots_rejects_invalid_input() {
+        let a = CString::new("nao e json").unwrap();
+
+        let result = take_json(unsafe { diff_state_snapshots(a.as_ptr(), std::ptr::null()) });
+        assert!(result.get("error").is_some());
+
+        let result = take_json(unsafe { diff_state_snapshots(a.as_ptr(), a.as_ptr()) });
+        assert!(result.get("error").is_some());
+    }
+}
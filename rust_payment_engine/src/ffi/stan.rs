@@ -0,0 +1,75 @@
+//! System Trace Audit Number (STAN) do terminal
+//!
+//! O EMV exige um número de rastreio por transação, de 6 dígitos, que volta
+//! para 1 após 999999. O contador é global ao processo.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Maior STAN válido antes de voltar para 1
+pub const MAX_STAN: u32 = 999_999;
+
+/// Último STAN emitido (0 = nenhum)
+static STAN: AtomicU32 = AtomicU32::new(0);
+
+/// Emite o próximo STAN (1..=999999, volta para 1 após o máximo)
+#[no_mangle]
+pub extern "C" fn next_stan() -> u32 {
+    let advance = |current: u32| Some(if current >= MAX_STAN { 1 } else { current + 1 });
+    match STAN.fetch_update(Ordering::SeqCst, Ordering::SeqCst, advance) {
+        Ok(previous) | Err(previous) => advance(previous).unwrap_or(1),
+    }
+}
+
+/// Último STAN emitido (0 se nenhum foi emitido)
+#[no_mangle]
+pub extern "C" fn current_stan() -> u32 {
+    STAN.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod stan_tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    /// Serializa os testes que dependem do contador global
+    static STAN_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_next_stan_increments() {
+        let _guard = STAN_LOCK.lock().unwrap();
+        STAN.store(41, Ordering::SeqCst);
+
+        assert_eq!(next_stan(), 42);
+        assert_eq!(next_stan(), 43);
+        assert_eq!(current_stan(), 43);
+    }
+
+    #[test]
+    fn test_next_stan_wraps_to_one() {
+        let _guard = STAN_LOCK.lock().unwrap();
+        STAN.store(MAX_STAN - 1, Ordering::SeqCst);
+
+        assert_eq!(next_stan(), MAX_STAN);
+        assert_eq!(next_stan(), 1);
+        assert_eq!(current_stan(), 1);
+    }
+
+    #[test]
+    fn test_next_stan_is_unique_under_concurrency() {
+        let _guard = STAN_LOCK.lock().unwrap();
+        STAN.store(0, Ordering::SeqCst);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| (0..500).map(|_| next_stan()).collect::<Vec<_>>()))
+            .collect();
+        let issued: Vec<u32> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+
+        let unique: HashSet<u32> = issued.iter().copied().collect();
+        assert_eq!(unique.len(), 4000);
+        assert_eq!(current_stan(), 4000);
+    }
+}
@@ -0,0 +1,433 @@
+//! Máquina de estados exposta via C ABI
+//!
+//! Alternativa ao flutter_rust_bridge para quem usa `dart:ffi` diretamente:
+//! o handle guarda a `RustPaymentApi` e um runtime tokio próprio, e cada
+//! chamada bloqueia a thread do chamador até a ação terminar.
+//!
+//! **Contrato de propriedade:** o handle retornado por `state_machine_new`
+//! pertence ao chamador e deve ser liberado exatamente uma vez com
+//! `state_machine_free`; depois disso o ponteiro não pode mais ser usado.
+//! As funções não devem ser chamadas de dentro de um runtime tokio.
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+use super::bin_table::payment_type_from_code;
+use crate::{RustPaymentApi, StateType};
+
+/// Ação executada
+pub const STATE_MACHINE_OK: i32 = 0;
+/// Handle nulo
+pub const STATE_MACHINE_NULL_HANDLE: i32 = -1;
+/// Ação rejeitada pelo estado atual (ex: valor inválido)
+pub const STATE_MACHINE_REJECTED: i32 = 1;
+/// Argumento inválido (ex: código de tipo de pagamento desconhecido)
+pub const STATE_MACHINE_INVALID_ARGUMENT: i32 = 2;
+
+/// Callback de mudança de estado: (estado de origem, estado de destino, timestamp RFC3339)
+///
+/// Estados seguem os códigos de `state_machine_current_state`. O timestamp
+/// só é válido durante a chamada; copie-o se precisar guardá-lo.
+pub type StateEventCallback = extern "C" fn(from: i32, to: i32, timestamp: *const c_char);
+
+/// Hook de entrada/saída de estado: recebe o código do estado
+pub type StateHookCallback = extern "C" fn(state: i32);
+
+/// Hook de entrada no estado (`register_state_hook`)
+pub const STATE_HOOK_ENTER: i32 = 0;
+/// Hook de saída do estado (`register_state_hook`)
+pub const STATE_HOOK_EXIT: i32 = 1;
+
+/// Máquina de estados com o runtime que executa suas ações
+pub struct StateMachineHandle {
+    runtime: Runtime,
+    api: RustPaymentApi,
+    /// Tarefa que entrega eventos ao callback registrado
+    event_task: Mutex<Option<JoinHandle<()>>>,
+    /// Eventos que o callback atual perdeu por atraso
+    missed_events: Arc<AtomicU64>,
+}
+
+impl StateMachineHandle {
+    /// Executa uma ação bloqueando até o fim, mapeando erro para código
+    fn run<F>(&self, action: F) -> i32
+    where
+        F: std::future::Future<Output = Result<String, String>>,
+    {
+        match self.runtime.block_on(action) {
+            Ok(_) => STATE_MACHINE_OK,
+            Err(_) => STATE_MACHINE_REJECTED,
+        }
+    }
+}
+
+/// Resolve o handle, ou None para ponteiro nulo
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+unsafe fn handle_ref<'a>(ptr: *mut StateMachineHandle) -> Option<&'a StateMachineHandle> {
+    ptr.as_ref()
+}
+
+/// Cria uma máquina de estados em AwaitingInfo
+///
+/// Retorna nulo se o runtime não puder ser criado. Liberar com
+/// `state_machine_free`.
+#[no_mangle]
+pub extern "C" fn state_machine_new() -> *mut StateMachineHandle {
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let api = {
+        let _guard = runtime.enter();
+        RustPaymentApi::new()
+    };
+    Box::into_raw(Box::new(StateMachineHandle {
+        runtime,
+        api,
+        event_task: Mutex::new(None),
+        missed_events: Arc::new(AtomicU64::new(0)),
+    }))
+}
+
+/// Define o valor do pagamento
+///
+/// Retorna `STATE_MACHINE_OK`, `STATE_MACHINE_REJECTED` ou
+/// `STATE_MACHINE_NULL_HANDLE`.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_set_amount(ptr: *mut StateMachineHandle, amount: f64) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    handle.run(handle.api.set_amount(amount))
+}
+
+/// Define o tipo de pagamento (0=Debit, 1=Credit, 2=Verification, 3=Pix)
+///
+/// Código desconhecido retorna `STATE_MACHINE_INVALID_ARGUMENT`.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_set_payment_type(ptr: *mut StateMachineHandle, payment_type: i32) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    let Ok(payment_type) = payment_type_from_code(payment_type) else {
+        return STATE_MACHINE_INVALID_ARGUMENT;
+    };
+    handle.run(handle.api.set_payment_type(payment_type))
+}
+
+/// Confirma as informações e inicia o pagamento (ConfirmInfo)
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_confirm(ptr: *mut StateMachineHandle) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    handle.run(handle.api.confirm_info())
+}
+
+/// Estado atual, na ordem de `StateType` (0=AwaitingInfo, 1=EMVPayment,
+/// 2=PaymentSuccess, 3=PaymentFailed, 4=CollectRemainder, 5=Voided,
+/// 6=Declined, 7=Refund, 8=PixPayment)
+///
+/// Retorna `STATE_MACHINE_NULL_HANDLE` para handle nulo.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_current_state(ptr: *mut StateMachineHandle) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    handle.runtime.block_on(handle.api.get_current_state()) as i32
+}
+
+/// Registra um callback chamado a cada mudança de estado
+///
+/// Uma tarefa do runtime do handle consome os eventos e chama `cb` para
+/// cada um, na ordem em que ocorreram. Um novo registro substitui o
+/// anterior. Recebe apenas eventos posteriores ao registro.
+///
+/// **Thread:** `cb` roda em uma thread de trabalho do tokio, não na thread
+/// que registrou. No Dart, use `NativeCallable.listener` (ou equivalente)
+/// para reencaminhar a chamada ao isolate correto; o callback não deve
+/// bloquear nem chamar de volta as funções `state_machine_*`.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`, e
+/// `cb` deve permanecer válido até `state_machine_free` ou um novo registro.
+#[no_mangle]
+pub unsafe extern "C" fn register_event_callback(ptr: *mut StateMachineHandle, cb: StateEventCallback) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    let mut events = handle.api.subscribe_events();
+    let missed_events = Arc::clone(&handle.missed_events);
+    missed_events.store(0, Ordering::Relaxed);
+    let task = handle.runtime.spawn(async move {
+        while let Some(event) = events.recv().await {
+            missed_events.store(events.missed_events(), Ordering::Relaxed);
+            // Vive até o fim da chamada e é liberado em seguida
+            let timestamp = CString::new(event.timestamp).unwrap_or_default();
+            cb(event.from_state as i32, event.to_state as i32, timestamp.as_ptr());
+        }
+    });
+
+    let mut event_task = handle.event_task.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(previous) = event_task.replace(task) {
+        previous.abort();
+    }
+    STATE_MACHINE_OK
+}
+
+/// Converte o código C de `StateType` (ver `state_machine_current_state`)
+fn state_type_from_code(code: i32) -> Option<StateType> {
+    match code {
+        0 => Some(StateType::AwaitingInfo),
+        1 => Some(StateType::EMVPayment),
+        2 => Some(StateType::PaymentSuccess),
+        3 => Some(StateType::PaymentFailed),
+        4 => Some(StateType::CollectRemainder),
+        5 => Some(StateType::Voided),
+        6 => Some(StateType::Declined),
+        7 => Some(StateType::Refund),
+        8 => Some(StateType::PixPayment),
+        _ => None,
+    }
+}
+
+/// Registra `cb` para rodar ao entrar (`STATE_HOOK_ENTER`) ou sair
+/// (`STATE_HOOK_EXIT`) do estado `state` (ex: ligar/desligar o leitor NFC)
+///
+/// Os hooks de todas as transições rodam em uma única thread dedicada, na
+/// ordem das transições: primeiro os de saída, depois os de entrada. Vários
+/// hooks podem ser registrados para o mesmo estado e não há como removê-los.
+/// Retorna `STATE_MACHINE_OK`, `STATE_MACHINE_INVALID_ARGUMENT` para estado
+/// ou tipo de hook desconhecido, ou `STATE_MACHINE_NULL_HANDLE`.
+///
+/// **Thread:** como em `register_event_callback`, `cb` não roda na thread
+/// que registrou e não deve bloquear nem chamar as funções `state_machine_*`.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`, e
+/// `cb` deve permanecer válido até o fim do processo: hooks já enfileirados
+/// podem rodar depois de `state_machine_free`.
+#[no_mangle]
+pub unsafe extern "C" fn register_state_hook(
+    ptr: *mut StateMachineHandle,
+    kind: i32,
+    state: i32,
+    cb: StateHookCallback,
+) -> i32 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE;
+    };
+    let Some(state) = state_type_from_code(state) else {
+        return STATE_MACHINE_INVALID_ARGUMENT;
+    };
+    let code = state as i32;
+    let hook = Arc::new(move || cb(code));
+    match kind {
+        STATE_HOOK_ENTER => handle.api.on_enter(state, hook),
+        STATE_HOOK_EXIT => handle.api.on_exit(state, hook),
+        _ => return STATE_MACHINE_INVALID_ARGUMENT,
+    }
+    STATE_MACHINE_OK
+}
+
+/// Eventos que o callback registrado perdeu por ficar atrasado
+///
+/// O callback recebe no máximo `EVENT_BROADCAST_CAPACITY` eventos de atraso;
+/// além disso os mais antigos são descartados e contados aqui. Zera a cada
+/// `register_event_callback`. Retorna `STATE_MACHINE_NULL_HANDLE` para
+/// handle nulo.
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle vivo criado por `state_machine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_missed_events(ptr: *mut StateMachineHandle) -> i64 {
+    let Some(handle) = handle_ref(ptr) else {
+        return STATE_MACHINE_NULL_HANDLE as i64;
+    };
+    handle.missed_events.load(Ordering::Relaxed) as i64
+}
+
+/// Libera o handle e encerra seu runtime
+///
+/// # Safety
+/// `ptr` deve ser nulo ou um handle criado por `state_machine_new` e
+/// ainda não liberado. Ponteiro nulo é ignorado.
+#[no_mangle]
+pub unsafe extern "C" fn state_machine_free(ptr: *mut StateMachineHandle) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod state_machine_tests {
+    use super::*;
+
+    #[test]
+    fn test_state_machine_confirm_flow() {
+        let ptr = state_machine_new();
+        assert!(!ptr.is_null());
+
+        unsafe {
+            assert_eq!(state_machine_current_state(ptr), StateType::AwaitingInfo as i32);
+            assert_eq!(state_machine_set_amount(ptr, 100.0), STATE_MACHINE_OK);
+            assert_eq!(state_machine_set_payment_type(ptr, 1), STATE_MACHINE_OK);
+            assert_eq!(state_machine_confirm(ptr), STATE_MACHINE_OK);
+            assert_eq!(state_machine_current_state(ptr), StateType::EMVPayment as i32);
+            state_machine_free(ptr);
+        }
+    }
+
+    #[test]
+    fn test_state_machine_error_codes() {
+        let ptr = state_machine_new();
+
+        unsafe {
+            assert_eq!(state_machine_set_amount(ptr, -5.0), STATE_MACHINE_REJECTED);
+            assert_eq!(state_machine_set_payment_type(ptr, 42), STATE_MACHINE_INVALID_ARGUMENT);
+            // Sem valor e tipo definidos, a confirmação é rejeitada
+            assert_eq!(state_machine_confirm(ptr), STATE_MACHINE_REJECTED);
+            assert_eq!(state_machine_current_state(ptr), StateType::AwaitingInfo as i32);
+            state_machine_free(ptr);
+        }
+    }
+
+    static CALLBACK_EVENTS: Mutex<Vec<(i32, i32, String)>> = Mutex::new(Vec::new());
+
+    extern "C" fn record_event(from: i32, to: i32, timestamp: *const c_char) {
+        let timestamp = unsafe { std::ffi::CStr::from_ptr(timestamp) }.to_str().unwrap().to_string();
+        CALLBACK_EVENTS.lock().unwrap().push((from, to, timestamp));
+    }
+
+    #[test]
+    fn test_event_callback_invoked_for_each_transition() {
+        let ptr = state_machine_new();
+
+        unsafe {
+            assert_eq!(register_event_callback(ptr, record_event), STATE_MACHINE_OK);
+            state_machine_set_amount(ptr, 100.0);
+            state_machine_set_payment_type(ptr, 0);
+            assert_eq!(state_machine_confirm(ptr), STATE_MACHINE_OK);
+
+            let handle = &*ptr;
+            handle.runtime.block_on(handle.api.process_payment()).unwrap();
+            handle.runtime
+                .block_on(handle.api.complete_payment("TXN-CB".to_string(), "AUTH".to_string()))
+                .unwrap();
+        }
+
+        // O callback roda em uma thread do tokio: aguarda as entregas
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while CALLBACK_EVENTS.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(unsafe { state_machine_missed_events(ptr) }, 0);
+        let events = CALLBACK_EVENTS.lock().unwrap().clone();
+        let transitions: Vec<_> = events.iter().map(|(from, to, _)| (*from, *to)).collect();
+        assert_eq!(transitions, [
+            (StateType::AwaitingInfo as i32, StateType::EMVPayment as i32),
+            (StateType::EMVPayment as i32, StateType::PaymentSuccess as i32),
+        ]);
+        assert!(events.iter().all(|(_, _, timestamp)| chrono::DateTime::parse_from_rfc3339(timestamp).is_ok()));
+
+        unsafe { state_machine_free(ptr) };
+    }
+
+    static HOOK_CALLS: Mutex<Vec<(&str, i32)>> = Mutex::new(Vec::new());
+
+    extern "C" fn record_exit(state: i32) {
+        HOOK_CALLS.lock().unwrap().push(("exit", state));
+    }
+
+    extern "C" fn record_enter(state: i32) {
+        HOOK_CALLS.lock().unwrap().push(("enter", state));
+    }
+
+    #[test]
+    fn test_state_hooks_run_exit_then_enter() {
+        let ptr = state_machine_new();
+        let awaiting_info = StateType::AwaitingInfo as i32;
+        let emv_payment = StateType::EMVPayment as i32;
+
+        unsafe {
+            assert_eq!(register_state_hook(ptr, STATE_HOOK_EXIT, awaiting_info, record_exit), STATE_MACHINE_OK);
+            assert_eq!(register_state_hook(ptr, STATE_HOOK_ENTER, emv_payment, record_enter), STATE_MACHINE_OK);
+            assert_eq!(register_state_hook(ptr, STATE_HOOK_ENTER, 42, record_enter), STATE_MACHINE_INVALID_ARGUMENT);
+            assert_eq!(register_state_hook(ptr, 7, emv_payment, record_enter), STATE_MACHINE_INVALID_ARGUMENT);
+
+            state_machine_set_amount(ptr, 100.0);
+            state_machine_set_payment_type(ptr, 0);
+            assert_eq!(state_machine_confirm(ptr), STATE_MACHINE_OK);
+        }
+
+        // Os hooks rodam na thread dedicada: aguarda as chamadas
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while HOOK_CALLS.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(*HOOK_CALLS.lock().unwrap(), [("exit", awaiting_info), ("enter", emv_payment)]);
+        unsafe { state_machine_free(ptr) };
+    }
+
+    #[test]
+    fn test_execute_json_process_payment_respects_velocity_rule() {
+        let ptr = state_machine_new();
+
+        unsafe {
+            state_machine_set_amount(ptr, 100.0);
+            state_machine_set_payment_type(ptr, 0);
+            assert_eq!(state_machine_confirm(ptr), STATE_MACHINE_OK);
+
+            let handle = &*ptr;
+            handle.api.set_velocity_rule(0, 60);
+            let process = r#"{"type":"EmvPayment","data":{"type":"ProcessPayment"}}"#.to_string();
+            let error = handle.runtime.block_on(handle.api.execute_json(process)).unwrap_err();
+            assert!(error.starts_with(crate::state_machine::velocity::VELOCITY_EXCEEDED));
+
+            // Outras ações não passam pela regra
+            let cancel = r#"{"type":"EmvPayment","data":{"type":"CancelPayment","data":{"reason":null}}}"#.to_string();
+            handle.runtime.block_on(handle.api.execute_json(cancel)).unwrap();
+            state_machine_free(ptr);
+        }
+    }
+
+    #[test]
+    fn test_state_machine_null_handle() {
+        let null = std::ptr::null_mut();
+
+        unsafe {
+            assert_eq!(state_machine_set_amount(null, 10.0), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(state_machine_set_payment_type(null, 0), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(state_machine_confirm(null), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(state_machine_current_state(null), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(register_event_callback(null, record_event), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(register_state_hook(null, STATE_HOOK_ENTER, 0, record_enter), STATE_MACHINE_NULL_HANDLE);
+            assert_eq!(state_machine_missed_events(null), STATE_MACHINE_NULL_HANDLE as i64);
+            state_machine_free(null);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Estatísticas de lotes de transações
+
+use anyhow::Result;
+use serde::Serialize;
+use std::os::raw::c_char;
+
+use super::json_result;
+
+/// Lê um array C de valores
+///
+/// # Safety
+/// `values` deve ser nulo (com `count == 0`) ou apontar para `count` f64.
+unsafe fn read_f64_slice<'a>(values: *const f64, count: usize) -> Result<&'a [f64]> {
+    if count == 0 {
+        return Ok(&[]);
+    }
+    if values.is_null() {
+        return Err(anyhow::anyhow!("Ponteiro nulo para {} valores", count));
+    }
+    Ok(std::slice::from_raw_parts(values, count))
+}
+
+/// Índices cujo z-score (desvio padrão populacional) excede `z_threshold`
+fn compute_outliers(amounts: &[f64], z_threshold: f64) -> Result<Vec<usize>> {
+    if !z_threshold.is_finite() || z_threshold <= 0.0 {
+        return Err(anyhow::anyhow!("Limite de z-score deve ser positivo"));
+    }
+    if let Some(idx) = amounts.iter().position(|a| !a.is_finite()) {
+        return Err(anyhow::anyhow!("Valor não finito no índice {}", idx));
+    }
+    if amounts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let n = amounts.len() as f64;
+    let mean = amounts.iter().sum::<f64>() / n;
+    let variance = amounts.iter().map(|a| (a - mean).powi(2)).sum::<f64>() / n;
+    let std_dev = variance.sqrt();
+    // Variância zero: todos iguais, nenhum valor se destaca
+    if std_dev == 0.0 {
+        return Ok(Vec::new());
+    }
+
+    Ok(amounts
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| ((*a - mean) / std_dev).abs() > z_threshold)
+        .map(|(idx, _)| idx)
+        .collect())
+}
+
+/// Aponta transações fora da distribuição do lote (revisão de fraude)
+///
+/// Retorna um array JSON com os índices cujo |z-score| excede `z_threshold`,
+/// calculado a partir da média e do desvio padrão do lote, ou `{"error": ...}`.
+///
+/// # Safety
+/// `amounts` deve apontar para `count` valores f64 (ou ser nulo com
+/// `count == 0`). O retorno deve ser liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn find_outliers(amounts: *const f64, count: usize, z_threshold: f64) -> *mut c_char {
+    json_result(read_f64_slice(amounts, count).and_then(|amounts| compute_outliers(amounts, z_threshold)))
+}
+
+/// Índice de chargeback de um período
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct ChargebackRatio {
+    ratio: f64,
+    over_threshold: bool,
+}
+
+/// `chargebacks / transactions`, comparado ao limite de alerta (fração)
+fn compute_chargeback_ratio(chargebacks: u64, transactions: u64, warn_threshold: f64) -> Result<ChargebackRatio> {
+    if !warn_threshold.is_finite() || warn_threshold < 0.0 {
+        return Err(anyhow::anyhow!("Limite de alerta inválido"));
+    }
+    // Sem vendas no período não há índice a penalizar
+    let ratio = if transactions == 0 {
+        0.0
+    } else {
+        chargebacks as f64 / transactions as f64
+    };
+    Ok(ChargebackRatio {
+        ratio,
+        over_threshold: ratio > warn_threshold,
+    })
+}
+
+/// Índice de chargeback do período e alerta de limite do adquirente
+///
+/// `warn_threshold` é uma fração (0.01 = 1%). Retorna
+/// `{ "ratio", "over_threshold" }`, com `ratio` 0 quando não houve
+/// transações, ou `{"error": ...}` para limite negativo ou não finito.
+/// Liberar com `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn chargeback_ratio(chargeback_count: u64, transaction_count: u64, warn_threshold: f64) -> *mut c_char {
+    json_result(compute_chargeback_ratio(chargeback_count, transaction_count, warn_threshold))
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+
+    #[test]
+    fn test_find_outliers_flags_clear_outlier() {
+        let amounts = [50.0, 52.0, 48.0, 51.0, 49.0, 50.0, 53.0, 47.0, 50.0, 5000.0];
+        let result = take_json(unsafe { find_outliers(amounts.as_ptr(), amounts.len(), 2.0) });
+
+        assert_eq!(result, serde_json::json!([9]));
+    }
+
+    #[test]
+    fn test_find_outliers_uniform_batch_has_none() {
+        let amounts = [25.0; 8];
+        let result = take_json(unsafe { find_outliers(amounts.as_ptr(), amounts.len(), 1.0) });
+
+        assert_eq!(result, serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_find_outliers_rejects_invalid_input() {
+        let result = take_json(unsafe { find_outliers(std::ptr::null(), 3, 2.0) });
+        assert!(result.get("error").is_some());
+
+        assert!(compute_outliers(&[1.0, f64::NAN], 2.0).is_err());
+        assert!(compute_outliers(&[1.0, 2.0], 0.0).is_err());
+        assert_eq!(compute_outliers(&[], 2.0).unwrap(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_chargeback_ratio_below_threshold() {
+        let result = take_json(chargeback_ratio(5, 1000, 0.01));
+
+        assert_eq!(result, serde_json::json!({ "ratio": 0.005, "over_threshold": false }));
+    }
+
+    #[test]
+    fn test_chargeback_ratio_above_threshold() {
+        let result = take_json(chargeback_ratio(15, 1000, 0.01));
+
+        assert_eq!(result["ratio"].as_f64().unwrap(), 0.015);
+        assert_eq!(result["over_threshold"], true);
+    }
+
+    #[test]
+    fn test_chargeback_ratio_zero_transactions() {
+        let result = compute_chargeback_ratio(0, 0, 0.01).unwrap();
+        assert_eq!(result, ChargebackRatio { ratio: 0.0, over_threshold: false });
+
+        let result = take_json(chargeback_ratio(3, 0, 0.01));
+        assert_eq!(result["ratio"].as_f64().unwrap(), 0.0);
+
+        assert!(compute_chargeback_ratio(1, 10, f64::NAN).is_err());
+        assert!(compute_chargeback_ratio(1, 10, -0.01).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Normalização de timestamps recebidos de fontes externas
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::os::raw::c_char;
+
+use super::{into_c_string, json_result, read_c_str};
+
+/// A partir deste valor um epoch numérico é tratado como milissegundos
+/// (1e11 segundos seria o ano 5138)
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Converte RFC3339 ou epoch (segundos/milissegundos) para RFC3339 UTC
+fn canonical_timestamp(input: &str) -> Result<String> {
+    let input = input.trim();
+    let parsed = if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+        let epoch: i64 = input
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Epoch fora do intervalo: {}", input))?;
+        let parsed = if epoch >= EPOCH_MILLIS_THRESHOLD {
+            DateTime::<Utc>::from_timestamp_millis(epoch)
+        } else {
+            DateTime::<Utc>::from_timestamp(epoch, 0)
+        };
+        parsed.ok_or_else(|| anyhow::anyhow!("Epoch fora do intervalo: {}", input))?
+    } else {
+        DateTime::parse_from_rfc3339(input)
+            .map_err(|_| anyhow::anyhow!("Timestamp inválido: {}", input))?
+            .with_timezone(&Utc)
+    };
+    Ok(parsed.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+/// Normaliza um timestamp para RFC3339 em UTC (ex: "2024-01-01T12:00:00Z")
+///
+/// Aceita RFC3339 com qualquer fuso e epoch em segundos ou milissegundos.
+/// Frações de segundo são preservadas. Retorna `{"error": ...}` para
+/// entrada nula ou não reconhecida.
+///
+/// # Safety
+/// `input` deve ser nulo ou uma string C válida. O retorno deve ser
+/// liberado com `free_rust_string`.
+#[no_mangle]
+pub unsafe extern "C" fn canonicalize_timestamp(input: *const c_char) -> *mut c_char {
+    let input = read_c_str(input).ok_or_else(|| anyhow::anyhow!("Timestamp não informado"));
+    match input.and_then(canonical_timestamp) {
+        Ok(timestamp) => into_c_string(timestamp),
+        Err(e) => json_result::<()>(Err(e)),
+    }
+}
+
+#[cfg(test)]
+mod timestamp_tests {
+    use super::*;
+    use crate::ffi::test_support::{take_json, take_string};
+    use std::ffi::CString;
+
+    fn canonicalize(input: &str) -> String {
+        let input = CString::new(input).unwrap();
+        take_string(unsafe { canonicalize_timestamp(input.as_ptr()) })
+    }
+
+    #[test]
+    fn test_canonicalize_rfc3339_to_utc() {
+        assert_eq!(canonicalize("2024-03-10T09:30:00-03:00"), "2024-03-10T12:30:00Z");
+        assert_eq!(canonicalize("2024-03-10T12:30:00.250Z"), "2024-03-10T12:30:00.250Z");
+    }
+
+    #[test]
+    fn test_canonicalize_epoch_seconds_and_millis() {
+        assert_eq!(canonicalize("1710073800"), "2024-03-10T12:30:00Z");
+        assert_eq!(canonicalize(" 1710073800250 "), "2024-03-10T12:30:00.250Z");
+    }
+
+    #[test]
+    fn test_canonicalize_rejects_bad_input() {
+        for input in ["", "10/03/2024 12:30", "2024-03-10", "99999999999999999999"] {
+            let input = CString::new(input).unwrap();
+            let result = take_json(unsafe { canonicalize_timestamp(input.as_ptr()) });
+            assert!(result.get("error").is_some());
+        }
+        let result = take_json(unsafe { canonicalize_timestamp(std::ptr::null()) });
+        assert!(result.get("error").is_some());
+    }
+}
@@ -0,0 +1,72 @@
+//! Cálculos de gorjeta
+
+use anyhow::Result;
+use serde::Serialize;
+use std::os::raw::c_char;
+
+use super::json_result;
+use crate::money::{from_cents, to_cents, Amount};
+
+/// Total arredondado para cima e a gorjeta implícita
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct RoundedTotal {
+    rounded_total: f64,
+    implied_tip: f64,
+}
+
+/// Arredonda `amount` para o próximo múltiplo de `step` (em centavos)
+fn compute_round_up(amount: f64, step: f64) -> Result<RoundedTotal> {
+    let amount = Amount::new(amount)?;
+    if !step.is_finite() || to_cents(step) <= 0 {
+        return Err(anyhow::anyhow!("Passo de arredondamento deve ser maior que zero"));
+    }
+
+    let step_cents = to_cents(step);
+    let amount_cents = amount.cents();
+    let rounded_cents = (amount_cents + step_cents - 1) / step_cents * step_cents;
+
+    Ok(RoundedTotal {
+        rounded_total: from_cents(rounded_cents),
+        implied_tip: from_cents(rounded_cents - amount_cents),
+    })
+}
+
+/// Arredonda o total para cima até o próximo múltiplo de `step`
+///
+/// Ex: "arredondar para os próximos R$ 5". Retorna
+/// `{ "rounded_total", "implied_tip" }` ou `{"error": ...}` para
+/// `step <= 0` ou `amount < 0`. Liberar com `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn round_up_total(amount: f64, step: f64) -> *mut c_char {
+    json_result(compute_round_up(amount, step))
+}
+
+#[cfg(test)]
+mod tip_tests {
+    use super::*;
+    use crate::ffi::test_support::take_json;
+
+    #[test]
+    fn test_round_up_on_step_has_zero_tip() {
+        let result = take_json(round_up_total(45.0, 5.0));
+
+        assert_eq!(result["rounded_total"].as_f64().unwrap(), 45.0);
+        assert_eq!(result["implied_tip"].as_f64().unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_round_up_below_step_adds_tip() {
+        let rounded = compute_round_up(42.30, 5.0).unwrap();
+        assert_eq!(rounded, RoundedTotal { rounded_total: 45.0, implied_tip: 2.7 });
+
+        let rounded = compute_round_up(0.1 + 0.2, 0.5).unwrap();
+        assert_eq!(rounded, RoundedTotal { rounded_total: 0.5, implied_tip: 0.2 });
+    }
+
+    #[test]
+    fn test_round_up_rejects_invalid_input() {
+        assert!(compute_round_up(10.0, 0.0).is_err());
+        assert!(compute_round_up(10.0, -5.0).is_err());
+        assert!(compute_round_up(-1.0, 5.0).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Identificadores de transação gerados no terminal
+//!
+//! Formato `TXN-<AAAAMMDDhhmmss>-<sequência>-<aleatório>`: a sequência é
+//! global ao processo (única dentro da execução) e o sufixo aleatório evita
+//! colisões entre execuções do app no mesmo segundo.
+
+use rand::Rng;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::into_c_string;
+
+/// Próximo número de sequência
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+fn new_transaction_id() -> String {
+    let seq = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    let suffix: u16 = rand::thread_rng().gen();
+    format!(
+        "TXN-{}-{:06}-{:04X}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        seq % 1_000_000,
+        suffix
+    )
+}
+
+/// Gera um ID de transação
+///
+/// Liberar com `free_rust_string`.
+#[no_mangle]
+pub extern "C" fn generate_transaction_id() -> *mut c_char {
+    into_c_string(new_transaction_id())
+}
+
+/// Gera `count` IDs de transação em uma única chamada (lotes offline)
+///
+/// Preenche `out[0..count]` e retorna a quantidade gerada, ou -1 se `out`
+/// for nulo ou `count` não couber em `i32`. Cada ID deve ser liberado
+/// individualmente com `free_rust_string`.
+///
+/// # Safety
+/// `out` deve ser nulo ou apontar para um array gravável de pelo menos
+/// `count` ponteiros.
+#[no_mangle]
+pub unsafe extern "C" fn generate_transaction_ids(count: usize, out: *mut *mut c_char) -> i32 {
+    let Ok(generated) = i32::try_from(count) else {
+        return -1;
+    };
+    if out.is_null() {
+        return -1;
+    }
+    for slot in std::slice::from_raw_parts_mut(out, count) {
+        *slot = into_c_string(new_transaction_id());
+    }
+    generated
+}
+
+#[cfg(test)]
+mod transaction_id_tests {
+    use super::*;
+    use crate::ffi::test_support::take_string;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_transaction_id_format() {
+        let id = take_string(generate_transaction_id());
+        let parts: Vec<&str> = id.split('-').collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "TXN");
+        assert_eq!(parts[1].len(), 14);
+        assert_eq!(parts[2].len(), 6);
+        assert_eq!(parts[3].len(), 4);
+    }
+
+    #[test]
+    fn test_generate_transaction_ids_fills_unique_batch() {
+        let mut out = vec![std::ptr::null_mut(); 1000];
+        let generated = unsafe { generate_transaction_ids(out.len(), out.as_mut_ptr()) };
+        assert_eq!(generated, 1000);
+
+        let ids: HashSet<String> = out.into_iter().map(take_string).collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_generate_transaction_ids_rejects_null_output() {
+        assert_eq!(unsafe { generate_transaction_ids(3, std::ptr::null_mut()) }, -1);
+    }
+}
@@ -1,6 +1,9 @@
 mod frb_generated; /* AUTO INJECTED BY flutter_rust_bridge. This line may not be accurate, and you can change it according to your needs. */
 mod state_machine;
 mod api;
+pub mod ffi;
+pub mod money;
+pub mod settlement;
 
 pub use api::RustPaymentApi;
 pub use state_machine::{StateType, PaymentType, StateChangeEvent};
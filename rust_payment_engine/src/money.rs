@@ -0,0 +1,143 @@
+//! Utilitários monetários compartilhados
+//!
+//! Valores trafegam como `f64` em reais; comparações e arredondamentos
+//! devem sempre passar por centavos inteiros para evitar erros de ponto
+//! flutuante (ex: `0.1 + 0.2 != 0.3`).
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Converte um valor em reais para centavos inteiros
+pub fn to_cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// Converte centavos inteiros para reais
+pub fn from_cents(cents: i64) -> f64 {
+    cents as f64 / 100.0
+}
+
+/// Compara dois valores com tolerância em centavos
+///
+/// Ambos são arredondados para centavos antes da comparação. Valores não
+/// finitos nunca são considerados iguais; tolerância negativa equivale a zero.
+pub fn amounts_equal(a: f64, b: f64, cents_tolerance: i64) -> bool {
+    if !a.is_finite() || !b.is_finite() {
+        return false;
+    }
+    (to_cents(a) - to_cents(b)).abs() <= cents_tolerance.max(0)
+}
+
+/// Valor monetário validado (finito e não negativo)
+///
+/// Só pode ser construído via `Amount::new`, então qualquer `Amount` em
+/// circulação já passou pela validação. A FFI continua recebendo `f64` e
+/// converte na fronteira.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Amount(f64);
+
+impl Amount {
+    /// Valor zero (usado em verificação de conta)
+    pub const ZERO: Amount = Amount(0.0);
+
+    /// Cria um valor validado, rejeitando NaN, infinito e negativos
+    pub fn new(value: f64) -> Result<Self> {
+        if !value.is_finite() {
+            return Err(anyhow::anyhow!("Valor inválido: {}", value));
+        }
+        if value < 0.0 {
+            return Err(anyhow::anyhow!("Valor não pode ser negativo: {:.2}", value));
+        }
+        // Normaliza -0.0 para 0.0
+        Ok(Self(value.abs()))
+    }
+
+    /// Valor em reais
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Indica se o valor é zero
+    pub fn is_zero(self) -> bool {
+        self.0 == 0.0
+    }
+
+    /// Valor em centavos inteiros
+    pub fn cents(self) -> i64 {
+        to_cents(self.0)
+    }
+}
+
+impl Default for Amount {
+    fn default() -> Self {
+        Amount::ZERO
+    }
+}
+
+impl TryFrom<f64> for Amount {
+    type Error = anyhow::Error;
+
+    fn try_from(value: f64) -> Result<Self> {
+        Amount::new(value)
+    }
+}
+
+impl From<Amount> for f64 {
+    fn from(amount: Amount) -> f64 {
+        amount.0
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod money_tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts_equal_exact() {
+        assert!(amounts_equal(100.0, 100.0, 0));
+        assert!(amounts_equal(0.1 + 0.2, 0.3, 0));
+    }
+
+    #[test]
+    fn test_amounts_equal_within_tolerance() {
+        assert!(amounts_equal(10.00, 10.01, 1));
+        assert!(amounts_equal(10.02, 10.00, 2));
+    }
+
+    #[test]
+    fn test_amounts_equal_outside_tolerance() {
+        assert!(!amounts_equal(10.00, 10.01, 0));
+        assert!(!amounts_equal(10.00, 10.05, 2));
+        assert!(!amounts_equal(f64::NAN, f64::NAN, 100));
+        assert!(!amounts_equal(10.00, 10.01, -5));
+    }
+
+    #[test]
+    fn test_amount_accepts_valid_values() {
+        assert_eq!(Amount::new(100.5).unwrap().value(), 100.5);
+        assert!(Amount::new(0.0).unwrap().is_zero());
+        assert!(Amount::new(-0.0).unwrap().is_zero());
+        assert_eq!(Amount::new(12.34).unwrap().cents(), 1234);
+    }
+
+    #[test]
+    fn test_amount_rejects_invalid_values() {
+        assert!(Amount::new(-0.01).is_err());
+        assert!(Amount::new(f64::NAN).is_err());
+        assert!(Amount::new(f64::INFINITY).is_err());
+        assert!(Amount::new(f64::NEG_INFINITY).is_err());
+    }
+
+    #[test]
+    fn test_amount_display_honors_precision() {
+        assert_eq!(format!("{:.2}", Amount::new(7.5).unwrap()), "7.50");
+    }
+}
@@ -0,0 +1,147 @@
+//! Submissão de lotes de transações para liquidação (fechamento de lote)
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Transação aprovada a ser enviada para liquidação
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionRecord {
+    pub transaction_id: String,
+    pub authorization_code: String,
+    pub amount: f64,
+    pub timestamp: String,
+}
+
+/// Resultado da submissão de uma transação
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitOutcome {
+    pub transaction_id: String,
+    /// `None` em caso de sucesso
+    pub error: Option<String>,
+}
+
+/// Relatório da submissão de um lote, na ordem de entrada
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub outcomes: Vec<SubmitOutcome>,
+}
+
+/// Função de submissão de uma transação (bloqueante, ex: chamada HTTP)
+pub type SubmitFn = Arc<dyn Fn(TransactionRecord) -> Result<()> + Send + Sync>;
+
+/// Submete um lote de transações com concorrência limitada
+///
+/// No máximo `concurrency` submissões rodam ao mesmo tempo (mínimo 1).
+/// Cada chamada de `submit` roda no pool bloqueante do tokio, então a
+/// closure pode fazer I/O síncrono. Falhas não interrompem o lote: cada
+/// transação é reportada individualmente.
+pub async fn submit_batch(
+    transactions: Vec<TransactionRecord>,
+    concurrency: usize,
+    submit: SubmitFn,
+) -> SubmitReport {
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+
+    let handles: Vec<_> = transactions
+        .into_iter()
+        .map(|record| {
+            let transaction_id = record.transaction_id.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let submit = Arc::clone(&submit);
+
+            let handle = tokio::spawn(async move {
+                let _permit = match semaphore.acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(e) => return Err(anyhow::anyhow!("Semáforo fechado: {}", e)),
+                };
+                match tokio::task::spawn_blocking(move || submit(record)).await {
+                    Ok(result) => result,
+                    Err(e) => Err(anyhow::anyhow!("Submissão abortada: {}", e)),
+                }
+            });
+            (transaction_id, handle)
+        })
+        .collect();
+
+    let mut outcomes = Vec::with_capacity(handles.len());
+    for (transaction_id, handle) in handles {
+        let error = match handle.await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) => Some(format!("Submissão abortada: {}", e)),
+        };
+        outcomes.push(SubmitOutcome { transaction_id, error });
+    }
+
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    SubmitReport {
+        succeeded: outcomes.len() - failed,
+        failed,
+        outcomes,
+    }
+}
+
+#[cfg(test)]
+mod settlement_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn record(index: usize) -> TransactionRecord {
+        TransactionRecord {
+            transaction_id: format!("TXN-{}", index),
+            authorization_code: format!("AUTH-{}", index),
+            amount: 10.0 * index as f64,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_batch_reports_failures_and_bounds_concurrency() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+
+        let submit: SubmitFn = {
+            let active = Arc::clone(&active);
+            let max_active = Arc::clone(&max_active);
+            Arc::new(move |record: TransactionRecord| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                max_active.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                active.fetch_sub(1, Ordering::SeqCst);
+
+                // Rejeita transações com valor múltiplo de 30
+                if record.amount as i64 % 30 == 0 {
+                    Err(anyhow::anyhow!("Recusada pelo adquirente"))
+                } else {
+                    Ok(())
+                }
+            })
+        };
+
+        let transactions: Vec<_> = (1..=20).map(record).collect();
+        let report = submit_batch(transactions, 3, submit).await;
+
+        // Índices 3, 6, 9, 12, 15, 18 falham
+        assert_eq!(report.failed, 6);
+        assert_eq!(report.succeeded, 14);
+        assert_eq!(report.outcomes.len(), 20);
+        assert_eq!(report.outcomes[0].transaction_id, "TXN-1");
+        assert!(report.outcomes[2].error.as_deref().unwrap().contains("Recusada"));
+        assert!(max_active.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn test_submit_batch_empty() {
+        let submit: SubmitFn = Arc::new(|_| Ok(()));
+        let report = submit_batch(Vec::new(), 4, submit).await;
+
+        assert_eq!(report.succeeded, 0);
+        assert_eq!(report.failed, 0);
+        assert!(report.outcomes.is_empty());
+    }
+}
@@ -1,9 +1,12 @@
 use anyhow::Result;
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
-use super::{StateManager, StateType, StateChangeEvent, initialize_registry};
+use tokio::sync::{Mutex, broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use super::{StateManager, StateHook, StateType, StateChangeEvent, StateEvent, StateAction, ActionOutcome, TransitionOutcome, MemoryBudget, initialize_registry};
 use super::states::*;
 use super::state_trait::PaymentState;
+use super::config::cardholder_confirmation_threshold;
 
 /// API pública para gerenciamento de estados de pagamento
 /// 
@@ -15,6 +18,14 @@ use super::state_trait::PaymentState;
 pub struct PaymentStateApi {
     manager: StateManager,
     event_receiver: Arc<Mutex<mpsc::UnboundedReceiver<StateChangeEvent>>>,
+    progress_sender: mpsc::UnboundedSender<u8>,
+    progress_receiver: Arc<Mutex<mpsc::UnboundedReceiver<u8>>>,
+    /// Receptor de `StateEvent`, presente só após `subscribe_state_events`
+    state_event_receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<StateEvent>>>>,
+    /// Tempo máximo em EMVPayment antes do cancelamento automático
+    emv_timeout: Arc<std::sync::Mutex<Option<Duration>>>,
+    /// Tempo máximo de processamento antes de `FailPayment(Timeout)`
+    processing_timeout: Arc<std::sync::Mutex<Option<Duration>>>,
 }
 
 impl PaymentStateApi {
@@ -28,22 +39,55 @@ impl PaymentStateApi {
         // Garante que o registry está inicializado
         initialize_registry();
         
-        let initial_state = AwaitingInfo {
-            amount: None,
-            payment_type: None,
-        };
+        let initial_state = AwaitingInfo::initial();
         
         let (manager, rx) = StateManager::new(
             Box::new(initial_state),
             StateType::AwaitingInfo,
         );
         
+        Self::from_manager(manager, rx)
+    }
+    
+    /// Retoma um fluxo salvo com `snapshot`
+    pub fn restore(json: &str) -> Result<Self> {
+        initialize_registry();
+        
+        let (manager, rx) = StateManager::restore(json)?;
+        Ok(Self::from_manager(manager, rx))
+    }
+    
+    fn from_manager(
+        manager: StateManager,
+        rx: mpsc::UnboundedReceiver<StateChangeEvent>,
+    ) -> Self {
+        let (progress_sender, progress_rx) = mpsc::unbounded_channel();
+        
         Self {
             manager,
             event_receiver: Arc::new(Mutex::new(rx)),
+            progress_sender,
+            progress_receiver: Arc::new(Mutex::new(progress_rx)),
+            state_event_receiver: Arc::new(Mutex::new(None)),
+            emv_timeout: Arc::new(std::sync::Mutex::new(None)),
+            processing_timeout: Arc::new(std::sync::Mutex::new(None)),
         }
     }
     
+    /// Cancela automaticamente o pagamento após `timeout` em EMVPayment
+    /// 
+    /// Cobre o cliente que desiste após `ConfirmInfo`: ao entrar em EMVPayment
+    /// um timer é iniciado e, se nenhuma transição ocorrer antes do prazo,
+    /// `CancelPayment` devolve a máquina para AwaitingInfo. Vale para as
+    /// próximas entradas em EMVPayment; `None` desativa.
+    pub fn set_emv_timeout(&self, timeout: Option<Duration>) {
+        *self.emv_timeout.lock().unwrap_or_else(|e| e.into_inner()) = timeout;
+    }
+    
+    fn emv_timeout(&self) -> Option<Duration> {
+        *self.emv_timeout.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
     /// Executa uma ação assíncrona de forma simplificada
     /// 
     /// # Exemplo
@@ -51,11 +95,175 @@ impl PaymentStateApi {
     /// api.execute(AwaitingInfoAction::SetAmount { amount: 100.0 }).await?;
     /// api.execute(AwaitingInfoAction::ConfirmInfo).await?;
     /// ```
-    pub async fn execute<A>(&self, action: A) -> Result<String>
+    pub async fn execute<A>(&self, action: A) -> Result<TransitionOutcome>
     where
-        A: 'static,
+        A: 'static + Clone + std::fmt::Debug,
     {
-        self.manager.execute(action).await
+        let starts_processing = (&action as &dyn std::any::Any)
+            .downcast_ref::<EmvPaymentAction>()
+            .is_some_and(|action| matches!(action, EmvPaymentAction::ProcessPayment));
+        let outcome = self.manager.execute(action).await?;
+        
+        if let Some(limit) = self.processing_timeout().filter(|_| starts_processing) {
+            schedule_if_unchanged(&self.manager, limit, self.manager.last_event_seq(), StateType::EMVPayment, EmvPaymentAction::FailPayment {
+                reason: FailureReason::Timeout,
+            });
+        }
+        
+        if let Some(timeout) = self.emv_timeout() {
+            if outcome.transitioned && outcome.new_state == StateType::EMVPayment {
+                let seq = self.manager.last_event_seq();
+                schedule_if_unchanged(&self.manager, timeout, seq, StateType::EMVPayment, EmvPaymentAction::CancelPayment {
+                    reason: Some("Tempo esgotado aguardando o cliente".to_string()),
+                });
+            }
+        }
+        
+        Ok(outcome)
+    }
+    
+    /// Falha automaticamente pagamentos travados no processamento
+    /// 
+    /// Cada `ProcessPayment` aceito inicia um timer: se a máquina continuar
+    /// no mesmo EMVPayment após `limit`, `FailPayment(Timeout)` é executado sem
+    /// depender de polling da UI. `None` desativa para os próximos pagamentos.
+    pub fn set_processing_timeout(&self, limit: Option<Duration>) {
+        *self.processing_timeout.lock().unwrap_or_else(|e| e.into_inner()) = limit;
+    }
+    
+    fn processing_timeout(&self) -> Option<Duration> {
+        *self.processing_timeout.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Executa uma `StateAction`, despachando a ação interna para o estado atual
+    pub async fn execute_action(&self, action: StateAction) -> Result<ActionOutcome> {
+        let outcome = match action {
+            StateAction::AwaitingInfo(action) => self.execute(action).await,
+            StateAction::EmvPayment(action) => self.execute(action).await,
+            StateAction::PaymentSuccess(action) => self.execute(action).await,
+            StateAction::PaymentFailed(action) => self.execute(action).await,
+            StateAction::CollectRemainder(action) => self.execute(action).await,
+            StateAction::Voided(action) => self.execute(action).await,
+            StateAction::Declined(action) => self.execute(action).await,
+            StateAction::Refund(action) => self.execute(action).await,
+            StateAction::PixPayment(action) => self.execute(action).await,
+        }?;
+        
+        Ok(ActionOutcome {
+            from_state: outcome.previous_state,
+            state: outcome.new_state,
+            transitioned: outcome.transitioned,
+            message: outcome.to_string(),
+        })
+    }
+    
+    /// Volta automaticamente para AwaitingInfo `after` depois de cada sucesso
+    /// 
+    /// Para quiosques: ao entrar em PaymentSuccess um timer é iniciado e, se
+    /// o operador não agir antes (estorno, nova cobrança, reset manual),
+    /// `Reset` é executado. O tempo é o do tokio, então testes podem usar
+    /// `tokio::time::pause`.
+    /// 
+    /// Se o assinante ficar para trás, o estado atual é relido e o timer é
+    /// iniciado caso a máquina esteja em PaymentSuccess.
+    /// 
+    /// Roda até que o `JoinHandle` retornado seja abortado.
+    pub fn enable_auto_reset(&self, after: Duration) -> JoinHandle<()> {
+        let manager = self.manager.clone();
+        let mut events = manager.subscribe();
+        
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        // A entrada em PaymentSuccess pode estar entre os eventos
+                        // perdidos: descarta o atraso e decide pelo estado atual
+                        events = events.resubscribe();
+                        let seq = manager.last_event_seq();
+                        if manager.get_current_state_type().await == StateType::PaymentSuccess {
+                            schedule_if_unchanged(&manager, after, seq, StateType::PaymentSuccess, PaymentSuccessAction::Reset);
+                        }
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if event.to_state == StateType::PaymentSuccess {
+                    schedule_if_unchanged(&manager, after, event.seq, StateType::PaymentSuccess, PaymentSuccessAction::Reset);
+                }
+            }
+        })
+    }
+    
+    /// Executa o fluxo inteiro de um pagamento como uma única chamada
+    /// 
+    /// Define tipo, valor e gorjeta, confirma, processa, confirma o portador
+    /// quando o total passa do limite configurado e conclui com o resultado
+    /// de `complete` (chamado só após o processamento iniciar). PIX é
+    /// recusado antes de qualquer ação: ele conclui por `ConfirmPixReceived`.
+    /// Retorna o estado final de sucesso ou o primeiro erro do fluxo; em caso
+    /// de erro a máquina permanece no estado onde a falha ocorreu.
+    pub async fn run_to_completion(
+        &self,
+        info: PaymentInfo,
+        complete: impl FnOnce() -> EmvResult,
+    ) -> Result<PaymentSuccess> {
+        if info.payment_type == PaymentType::Pix {
+            return Err(anyhow::anyhow!("Pagamento PIX não é concluído por run_to_completion"));
+        }
+        
+        self.execute(AwaitingInfoAction::SetPaymentType { payment_type: info.payment_type.clone() }).await?;
+        self.execute(AwaitingInfoAction::SetAmount { amount: info.amount.value() }).await?;
+        if !info.tip.is_zero() {
+            self.execute(AwaitingInfoAction::SetTip { tip: info.tip.value() }).await?;
+        }
+        self.execute(AwaitingInfoAction::ConfirmInfo).await?;
+        self.execute(EmvPaymentAction::ProcessPayment).await?;
+        if info.total().value() > cardholder_confirmation_threshold() {
+            self.execute(EmvPaymentAction::ConfirmCardholder).await?;
+        }
+        self.execute(EmvPaymentAction::CompletePayment { result: Some(complete()) }).await?;
+        
+        self.manager.inspect(|state: &PaymentSuccess| state.clone()).await
+    }
+    
+    /// Registra um efeito ao entrar em `state` (ex: bipe no sucesso)
+    pub fn on_enter(&self, state: StateType, hook: StateHook) {
+        self.manager.on_enter(state, hook);
+    }
+    
+    /// Registra um efeito ao sair de `state` (ex: desligar o leitor NFC)
+    pub fn on_exit(&self, state: StateType, hook: StateHook) {
+        self.manager.on_exit(state, hook);
+    }
+    
+    /// Ações válidas no estado atual, para a UI decidir quais botões exibir
+    pub async fn available_actions(&self) -> Result<Vec<String>> {
+        self.manager.available_actions().await
+    }
+    
+    /// Serializa o estado atual como JSON (ver `restore`)
+    pub async fn snapshot(&self) -> Result<String> {
+        self.manager.snapshot().await
+    }
+    
+    /// Transação atual como JSON: `{ "state", "payment_info" }`
+    /// 
+    /// `payment_info` vem do estado serializado pelo registry e é omitido
+    /// enquanto não foi confirmado (AwaitingInfo). Não inclui tarifas: o
+    /// motor não calcula MDR.
+    pub async fn current_transaction_json(&self) -> Result<String> {
+        let persisted = self.manager.persisted_state().await?;
+        let mut transaction = serde_json::json!({ "state": persisted.state_type });
+        if let Some(payment_info) = persisted.state.get("payment_info") {
+            transaction["payment_info"] = payment_info.clone();
+        }
+        Ok(transaction.to_string())
+    }
+    
+    /// Desfaz a última transição, se reversível (correção de erro do operador)
+    pub async fn undo(&self) -> Result<String> {
+        self.manager.undo().await
     }
     
     /// Retorna o tipo do estado atual
@@ -63,11 +271,17 @@ impl PaymentStateApi {
         self.manager.get_current_state_type().await
     }
     
+    /// Indica se o fluxo terminou (ex: PaymentSuccess)
+    pub async fn is_terminal_state(&self) -> bool {
+        self.manager.is_terminal().await
+    }
+    
     /// Aguarda o próximo evento de mudança de estado
     /// 
     /// Retorna `None` se o canal foi fechado
     pub async fn next_event(&self) -> Option<StateChangeEvent> {
-        self.event_receiver.lock().await.recv().await
+        let event = self.event_receiver.lock().await.recv().await?;
+        Some(self.manager.resolve_superseded(event).await)
     }
     
     /// Tenta receber um evento sem bloquear
@@ -76,12 +290,117 @@ impl PaymentStateApi {
     /// `Ok(None)` se não houver eventos,
     /// `Err(())` se o canal foi fechado
     pub async fn try_next_event(&self) -> Result<Option<StateChangeEvent>, ()> {
-        self.event_receiver.lock().await.try_recv()
-            .map(Some)
-            .or_else(|e| match e {
-                mpsc::error::TryRecvError::Empty => Ok(None),
-                mpsc::error::TryRecvError::Disconnected => Err(()),
-            })
+        let received = self.event_receiver.lock().await.try_recv();
+        match received {
+            Ok(event) => Ok(Some(self.manager.resolve_superseded(event).await)),
+            Err(mpsc::error::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::error::TryRecvError::Disconnected) => Err(()),
+        }
+    }
+    
+    /// Passa a registrar `StateEvent` (mudanças de estado e ações rejeitadas)
+    /// 
+    /// Canal independente de `next_event`, que continua emitindo apenas
+    /// mudanças de estado. Desativado por padrão: sem assinatura nada é
+    /// acumulado. Eventos ainda não lidos de uma assinatura anterior são
+    /// descartados.
+    pub async fn subscribe_state_events(&self) {
+        *self.state_event_receiver.lock().await = Some(self.manager.subscribe_state_events());
+    }
+    
+    /// Para de registrar `StateEvent`, descartando os pendentes
+    pub async fn unsubscribe_state_events(&self) {
+        // Sem receptor o manager desativa o canal no próximo evento
+        *self.state_event_receiver.lock().await = None;
+    }
+    
+    /// Retorna (sem bloquear) os `StateEvent` pendentes, em ordem
+    /// 
+    /// Vazio enquanto `subscribe_state_events` não foi chamado.
+    pub async fn pending_state_events(&self) -> Vec<StateEvent> {
+        let mut receiver = self.state_event_receiver.lock().await;
+        let mut events = Vec::new();
+        while let Some(event) = receiver.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            events.push(event);
+        }
+        events
+    }
+    
+    /// Cria um assinante independente dos eventos de mudança de estado
+    /// 
+    /// Cada assinante recebe todos os eventos emitidos após a assinatura,
+    /// sem competir com `next_event` nem com outros assinantes.
+    pub fn subscribe(&self) -> EventSubscriber {
+        EventSubscriber {
+            manager: self.manager.clone(),
+            receiver: self.manager.subscribe(),
+            missed: 0,
+        }
+    }
+    
+    /// Reporta o progresso do processamento EMV (0–100)
+    /// 
+    /// Só é aceito durante o processamento e nunca regride. Valores aceitos
+    /// são publicados no canal de progresso, separado dos eventos de estado.
+    pub async fn report_progress(&self, percent: u8) -> Result<()> {
+        self.manager.execute(EmvPaymentAction::ReportProgress { percent }).await?;
+        self.progress_sender
+            .send(percent)
+            .map_err(|e| anyhow::anyhow!("Falha ao publicar progresso: {}", e))
+    }
+    
+    /// Retorna (sem bloquear) as atualizações de progresso pendentes, em ordem
+    pub async fn progress_events(&self) -> Vec<u8> {
+        let mut receiver = self.progress_receiver.lock().await;
+        let mut events = Vec::new();
+        while let Ok(percent) = receiver.try_recv() {
+            events.push(percent);
+        }
+        events
+    }
+    
+    /// Retorna a linha do tempo do fluxo como JSON
+    /// 
+    /// Array ordenado de `{ from, to, at, duration_ms }`, onde `duration_ms`
+    /// é o tempo de permanência em `from`. Útil para overlays de debug e
+    /// exportação para o suporte.
+    pub async fn flow_timeline_json(&self) -> String {
+        let timeline = self.manager.timeline().await;
+        serde_json::to_string(&timeline).unwrap_or_else(|_| "[]".to_string())
+    }
+    
+    /// Histórico de transições do fluxo (para reconstrução em disputas)
+    pub async fn history(&self) -> Vec<StateChangeEvent> {
+        self.manager.history().await
+    }
+    
+    /// Últimos eventos emitidos (buffer de replay), do mais antigo ao mais recente
+    pub async fn recent_events(&self) -> Vec<StateChangeEvent> {
+        self.manager.recent_events().await
+    }
+    
+    /// Limita a memória retida em sessões longas
+    /// 
+    /// `max_events` limita o buffer de replay e `max_history` a linha do tempo
+    /// de transições; ao exceder, as entradas mais antigas são descartadas.
+    /// Padrões: `DEFAULT_MAX_EVENTS` e `DEFAULT_MAX_HISTORY`.
+    pub async fn set_memory_budget(&self, max_events: usize, max_history: usize) {
+        self.manager
+            .set_memory_budget(MemoryBudget { max_events, max_history, ..MemoryBudget::default() })
+            .await
+    }
+    
+    /// Marca um evento (pelo `seq`) como obsoleto antes de ser consumido
+    /// 
+    /// Útil quando a UI já reagiu a uma transição posterior e o evento
+    /// anterior, ainda na fila, não representa mais o estado corrente.
+    pub async fn mark_superseded(&self, seq: u64) {
+        self.manager.mark_superseded(seq).await
+    }
+    
+    /// Obtém a descrição do estado atual, qualquer que seja ele
+    pub async fn current_description(&self) -> Result<String> {
+        self.manager.current_description().await
     }
     
     /// Obtém descrição do estado atual (se disponível)
@@ -98,6 +417,81 @@ impl PaymentStateApi {
     pub async fn get_payment_success_description(&self) -> Result<String> {
         self.manager.get_description::<PaymentSuccess, _>(|state| state.description()).await
     }
+    
+    /// Payload do QR code PIX a exibir (apenas em PixPayment)
+    pub async fn pix_qr_payload(&self) -> Result<String> {
+        self.manager.inspect(|state: &PixPayment| state.qr_payload.clone()).await
+    }
+}
+
+/// Executa `action` após `delay` se nada mudou desde o evento `seq`
+/// 
+/// O seq funciona como geração: qualquer transição posterior (conclusão,
+/// cancelamento, novo pagamento) invalida o timer, que então não age sobre
+/// a transação seguinte.
+fn schedule_if_unchanged<A>(manager: &StateManager, delay: Duration, seq: u64, state: StateType, action: A)
+where
+    A: 'static + Send + Clone + std::fmt::Debug,
+{
+    let manager = manager.clone();
+    
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        
+        if manager.last_event_seq() != seq || manager.get_current_state_type().await != state {
+            return;
+        }
+        // Ignora erro: ex. o processamento pode já ter passado da janela de cancelamento
+        let _ = manager.execute(action).await;
+    });
+}
+
+/// Assinante de eventos criado por `PaymentStateApi::subscribe`
+/// 
+/// Se o consumidor ficar para trás, os eventos mais antigos são descartados
+/// e contabilizados em `missed_events`, e a leitura continua do mais antigo
+/// ainda disponível.
+pub struct EventSubscriber {
+    manager: StateManager,
+    receiver: broadcast::Receiver<StateChangeEvent>,
+    missed: u64,
+}
+
+impl EventSubscriber {
+    /// Aguarda o próximo evento
+    /// 
+    /// Retorna `None` se o canal foi fechado
+    pub async fn recv(&mut self) -> Option<StateChangeEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(self.manager.peek_superseded(event).await),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+    
+    /// Tenta receber um evento sem bloquear
+    /// 
+    /// Retorna `Ok(Some(event))` se houver evento disponível,
+    /// `Ok(None)` se não houver eventos,
+    /// `Err(())` se o canal foi fechado
+    #[cfg(test)]
+    pub async fn try_recv(&mut self) -> Result<Option<StateChangeEvent>, ()> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => return Ok(Some(self.manager.peek_superseded(event).await)),
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(broadcast::error::TryRecvError::Empty) => return Ok(None),
+                Err(broadcast::error::TryRecvError::Closed) => return Err(()),
+            }
+        }
+    }
+    
+    /// Total de eventos perdidos por atraso desde a assinatura
+    pub fn missed_events(&self) -> u64 {
+        self.missed
+    }
 }
 
 impl Default for PaymentStateApi {
@@ -110,6 +504,7 @@ impl Default for PaymentStateApi {
 mod api_tests {
     use super::*;
     use tokio::time::{timeout, Duration};
+    use super::super::state_manager::{DEFAULT_MAX_EVENTS, EVENT_BROADCAST_CAPACITY};
     
     #[tokio::test]
     async fn test_api_initialization() {
@@ -231,7 +626,7 @@ mod api_tests {
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
         
-        api.execute(EmvPaymentAction::CompletePayment { result: emv_result }).await.unwrap();
+        api.execute(EmvPaymentAction::CompletePayment { result: Some(emv_result) }).await.unwrap();
         
         assert_eq!(api.current_state().await, StateType::PaymentSuccess);
         
@@ -242,4 +637,545 @@ mod api_tests {
         let event2 = api.next_event().await.unwrap();
         assert_eq!(event2.to_state, StateType::PaymentSuccess);
     }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_watchdog_fails_stuck_processing() {
+        let api = PaymentStateApi::new();
+        
+        api.execute(AwaitingInfoAction::SetAmount { amount: 120.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Credit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        api.set_processing_timeout(Some(Duration::from_secs(30)));
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        // Antes do limite, permanece processando
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+        
+        // Depois do limite, o watchdog falha o pagamento
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(api.current_state().await, StateType::PaymentFailed);
+        
+        let event1 = api.next_event().await.unwrap();
+        assert_eq!(event1.to_state, StateType::EMVPayment);
+        
+        let event2 = api.next_event().await.unwrap();
+        assert_eq!(event2.from_state, StateType::EMVPayment);
+        assert_eq!(event2.to_state, StateType::PaymentFailed);
+        
+        let description = api.current_description().await.unwrap();
+        assert!(description.contains("tempo limite"));
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_watchdog_spares_completed_payment() {
+        let api = PaymentStateApi::new();
+        api.set_processing_timeout(Some(Duration::from_secs(30)));
+        
+        confirm_payment(&api).await;
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        api.execute(EmvPaymentAction::CompletePayment { result: Some(EmvResult {
+            transaction_id: "TXN-OK".to_string(),
+            authorization_code: "AUTH".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })}).await.unwrap();
+        
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(api.current_state().await, StateType::PaymentSuccess);
+    }
+    
+    #[tokio::test]
+    async fn test_api_run_to_completion_success() {
+        let api = PaymentStateApi::new();
+        let info = PaymentInfo {
+            amount: crate::money::Amount::new(150.0).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: crate::money::Amount::new(15.0).unwrap(),
+        };
+        
+        let success = api.run_to_completion(info, || EmvResult {
+            transaction_id: "TXN-RUN".to_string(),
+            authorization_code: "AUTH-RUN".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }).await.unwrap();
+        
+        assert_eq!(api.current_state().await, StateType::PaymentSuccess);
+        assert_eq!(success.result.transaction_id, "TXN-RUN");
+        assert_eq!(success.payment_info.amount.value(), 150.0);
+        assert_eq!(success.payment_info.tip.value(), 15.0);
+        assert_eq!(success.payment_info.payment_type, PaymentType::Credit);
+    }
+    
+    #[tokio::test]
+    async fn test_api_run_to_completion_invalid_amount_fails_early() {
+        let api = PaymentStateApi::new();
+        let info = PaymentInfo {
+            amount: crate::money::Amount::ZERO,
+            payment_type: PaymentType::Debit,
+            tip: crate::money::Amount::ZERO,
+        };
+        let mut completed = false;
+        
+        let result = api.run_to_completion(info, || {
+            completed = true;
+            EmvResult {
+                transaction_id: "TXN-NUNCA".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }
+        }).await;
+        
+        assert!(result.unwrap_err().to_string().contains("maior que zero"));
+        assert!(!completed);
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+    }
+    
+    fn run_result() -> EmvResult {
+        EmvResult {
+            transaction_id: "TXN-RUN".to_string(),
+            authorization_code: "AUTH-RUN".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_api_run_to_completion_confirms_cardholder_above_threshold() {
+        let api = PaymentStateApi::new();
+        // Valor no limite; só a gorjeta faz o total exigir a confirmação
+        let info = PaymentInfo {
+            amount: crate::money::Amount::new(5000.0).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: crate::money::Amount::new(100.0).unwrap(),
+        };
+        
+        let success = api.run_to_completion(info, run_result).await.unwrap();
+        
+        assert_eq!(success.payment_info.total().value(), 5100.0);
+        assert_eq!(api.current_state().await, StateType::PaymentSuccess);
+    }
+    
+    #[tokio::test]
+    async fn test_api_run_to_completion_card_verification() {
+        let api = PaymentStateApi::new();
+        // Verificação de cartão percorre o fluxo inteiro com valor zero
+        let info = PaymentInfo {
+            amount: crate::money::Amount::ZERO,
+            payment_type: PaymentType::Verification,
+            tip: crate::money::Amount::ZERO,
+        };
+        
+        api.run_to_completion(info, run_result).await.unwrap();
+        assert_eq!(api.current_state().await, StateType::PaymentSuccess);
+    }
+    
+    #[tokio::test]
+    async fn test_api_run_to_completion_rejects_pix_up_front() {
+        let api = PaymentStateApi::new();
+        let info = PaymentInfo {
+            amount: crate::money::Amount::new(50.0).unwrap(),
+            payment_type: PaymentType::Pix,
+            tip: crate::money::Amount::ZERO,
+        };
+        
+        let error = api.run_to_completion(info, run_result).await.unwrap_err();
+        assert!(error.to_string().contains("PIX"));
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+        assert!(api.manager.history().await.is_empty());
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_auto_reset_after_success() {
+        let api = PaymentStateApi::new();
+        let auto_reset = api.enable_auto_reset(Duration::from_secs(5));
+        
+        confirm_payment(&api).await;
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        api.execute(EmvPaymentAction::CompletePayment { result: Some(EmvResult {
+            transaction_id: "TXN-KIOSK".to_string(),
+            authorization_code: "AUTH".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })}).await.unwrap();
+        
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(api.current_state().await, StateType::PaymentSuccess);
+        
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+        auto_reset.abort();
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_auto_reset_survives_lagged_subscriber() {
+        let api = PaymentStateApi::new();
+        let auto_reset = api.enable_auto_reset(Duration::from_secs(5));
+        
+        // Sem ceder ao agendador, o timer fica mais eventos atrás que o buffer
+        tokio::task::unconstrained(async {
+            for _ in 0..EVENT_BROADCAST_CAPACITY {
+                confirm_payment(&api).await;
+                api.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+            }
+            confirm_payment(&api).await;
+            api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+            api.execute(EmvPaymentAction::CompletePayment { result: Some(run_result()) }).await.unwrap();
+        }).await;
+        
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+        auto_reset.abort();
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_auto_reset_skipped_when_operator_acts() {
+        let api = PaymentStateApi::new();
+        let auto_reset = api.enable_auto_reset(Duration::from_secs(5));
+        
+        confirm_payment(&api).await;
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        api.execute(EmvPaymentAction::CompletePayment { result: Some(EmvResult {
+            transaction_id: "TXN-KIOSK".to_string(),
+            authorization_code: "AUTH".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })}).await.unwrap();
+        
+        // Operador estorna antes do prazo; o reset não interfere no estorno
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        api.execute(PaymentSuccessAction::RefundPayment { amount: 80.0 }).await.unwrap();
+        
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert_eq!(api.current_state().await, StateType::Refund);
+        auto_reset.abort();
+    }
+    
+    async fn confirm_payment(api: &PaymentStateApi) {
+        api.execute(AwaitingInfoAction::SetAmount { amount: 80.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Debit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_timeout_cancels_abandoned_payment() {
+        let api = PaymentStateApi::new();
+        api.set_emv_timeout(Some(Duration::from_secs(30)));
+        confirm_payment(&api).await;
+        
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+        
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+        
+        let _ = api.next_event().await.unwrap();
+        let event = api.next_event().await.unwrap();
+        assert_eq!(event.from_state, StateType::EMVPayment);
+        assert_eq!(event.to_state, StateType::AwaitingInfo);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_timeout_does_not_cancel_next_transaction() {
+        let api = PaymentStateApi::new();
+        api.set_emv_timeout(Some(Duration::from_secs(30)));
+        confirm_payment(&api).await;
+        
+        // Primeiro pagamento cancelado pelo operador; um novo começa em seguida
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        api.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+        confirm_payment(&api).await;
+        
+        // O timer do primeiro pagamento expira sem afetar o segundo
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+        
+        // O timer do segundo pagamento continua valendo
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(api.current_state().await, StateType::AwaitingInfo);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_emv_timeout_can_be_disabled() {
+        let api = PaymentStateApi::new();
+        api.set_emv_timeout(Some(Duration::from_secs(30)));
+        api.set_emv_timeout(None);
+        confirm_payment(&api).await;
+        
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_without_timeout_keeps_emv_payment() {
+        let api = PaymentStateApi::new();
+        confirm_payment(&api).await;
+        
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_api_flow_timeline_json() {
+        let api = PaymentStateApi::new();
+        
+        api.execute(AwaitingInfoAction::SetAmount { amount: 50.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Debit 
+        }).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        api.execute(EmvPaymentAction::CompletePayment { result: Some(EmvResult {
+            transaction_id: "TXN1".to_string(),
+            authorization_code: "AUTH1".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })}).await.unwrap();
+        api.execute(PaymentSuccessAction::Reset).await.unwrap();
+        
+        let timeline: serde_json::Value = serde_json::from_str(&api.flow_timeline_json().await).unwrap();
+        let entries = timeline.as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        
+        let pairs: Vec<(&str, &str)> = entries.iter()
+            .map(|e| (e["from"].as_str().unwrap(), e["to"].as_str().unwrap()))
+            .collect();
+        assert_eq!(pairs, vec![
+            ("AwaitingInfo", "EMVPayment"),
+            ("EMVPayment", "PaymentSuccess"),
+            ("PaymentSuccess", "AwaitingInfo"),
+        ]);
+        
+        assert_eq!(entries[0]["duration_ms"].as_u64().unwrap(), 1500);
+        assert_eq!(entries[1]["duration_ms"].as_u64().unwrap(), 250);
+        assert!(entries.iter().all(|e| e["at"].as_str().is_some()));
+    }
+    
+    #[tokio::test]
+    async fn test_api_mark_superseded_on_rapid_round_trip() {
+        let api = PaymentStateApi::new();
+        
+        // A -> B -> A em sequência rápida, sem consumir eventos
+        api.execute(AwaitingInfoAction::SetAmount { amount: 80.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Debit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        api.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        
+        // A UI já sabe que voltou para AwaitingInfo: a ida para EMVPayment é obsoleta
+        api.mark_superseded(1).await;
+        
+        let event1 = api.next_event().await.unwrap();
+        assert_eq!(event1.seq, 1);
+        assert_eq!(event1.to_state, StateType::EMVPayment);
+        assert!(event1.superseded);
+        
+        let event2 = api.next_event().await.unwrap();
+        assert_eq!(event2.seq, 2);
+        assert_eq!(event2.to_state, StateType::AwaitingInfo);
+        assert!(!event2.superseded);
+    }
+
+    
+    #[tokio::test]
+    async fn test_api_progress_events_arrive_in_order() {
+        let api = PaymentStateApi::new();
+        
+        api.execute(AwaitingInfoAction::SetAmount { amount: 90.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Credit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        // Antes do processamento não há progresso
+        assert!(api.report_progress(10).await.is_err());
+        
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        for percent in [0, 25, 50, 50, 100] {
+            api.report_progress(percent).await.unwrap();
+        }
+        
+        // Fora do intervalo ou regredindo: rejeitado e não publicado
+        assert!(api.report_progress(101).await.is_err());
+        assert!(api.report_progress(80).await.is_err());
+        
+        let events = api.progress_events().await;
+        assert_eq!(events, vec![0, 25, 50, 50, 100]);
+        assert!(events.windows(2).all(|w| w[0] <= w[1]));
+        assert!(api.progress_events().await.is_empty());
+        assert_eq!(api.current_state().await, StateType::EMVPayment);
+    }
+
+    
+    #[tokio::test]
+    async fn test_api_memory_budget_keeps_most_recent_entries() {
+        let api = PaymentStateApi::new();
+        api.set_memory_budget(3, 2).await;
+        
+        // 5 idas e voltas = 10 transições
+        for _ in 0..5 {
+            api.execute(AwaitingInfoAction::SetAmount { amount: 10.0 }).await.unwrap();
+            api.execute(AwaitingInfoAction::SetPaymentType { 
+                payment_type: PaymentType::Debit 
+            }).await.unwrap();
+            api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+            api.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        }
+        
+        let seqs: Vec<u64> = api.recent_events().await.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![8, 9, 10]);
+        
+        let timeline: Vec<serde_json::Value> =
+            serde_json::from_str(&api.flow_timeline_json().await).unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[1]["to"], "AwaitingInfo");
+        
+        // Reduzir o orçamento descarta o excedente imediatamente
+        api.set_memory_budget(1, 1).await;
+        let seqs: Vec<u64> = api.recent_events().await.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![10]);
+    }
+    
+    #[tokio::test]
+    async fn test_state_events_report_failures() {
+        let api = PaymentStateApi::new();
+        api.subscribe_state_events().await;
+        
+        assert!(api.execute(AwaitingInfoAction::SetAmount { amount: -1.0 }).await.is_err());
+        api.execute(AwaitingInfoAction::SetAmount { amount: 50.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Credit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        let events = api.pending_state_events().await;
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            StateEvent::Failed { state, action, .. } => {
+                assert_eq!(*state, StateType::AwaitingInfo);
+                assert!(action.contains("SetAmount"));
+            }
+            other => panic!("esperado Failed, obtido {:?}", other),
+        }
+        assert!(matches!(events[1], StateEvent::Changed(_)));
+        
+        // O canal original continua só com mudanças de estado
+        assert!(api.try_next_event().await.unwrap().is_some());
+        assert!(api.try_next_event().await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_state_events_are_only_kept_while_subscribed() {
+        let api = PaymentStateApi::new();
+        
+        assert!(api.execute(AwaitingInfoAction::SetAmount { amount: -1.0 }).await.is_err());
+        api.subscribe_state_events().await;
+        assert!(api.pending_state_events().await.is_empty());
+        
+        assert!(api.execute(AwaitingInfoAction::SetAmount { amount: -2.0 }).await.is_err());
+        api.unsubscribe_state_events().await;
+        assert!(api.execute(AwaitingInfoAction::SetAmount { amount: -3.0 }).await.is_err());
+        assert!(api.pending_state_events().await.is_empty());
+        
+        api.subscribe_state_events().await;
+        assert!(api.execute(AwaitingInfoAction::SetAmount { amount: -4.0 }).await.is_err());
+        assert_eq!(api.pending_state_events().await.len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_subscribers_each_receive_every_event() {
+        let api = PaymentStateApi::new();
+        let mut audit = api.subscribe();
+        let mut ui = api.subscribe();
+        
+        api.execute(AwaitingInfoAction::SetAmount { amount: 80.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Debit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        for subscriber in [&mut audit, &mut ui] {
+            let event = subscriber.try_recv().await.unwrap().unwrap();
+            assert_eq!(event.to_state, StateType::EMVPayment);
+            assert!(subscriber.try_recv().await.unwrap().is_none());
+        }
+        
+        // O canal original continua funcionando
+        let event = api.try_next_event().await.unwrap().unwrap();
+        assert_eq!(event.to_state, StateType::EMVPayment);
+    }
+    
+    #[tokio::test]
+    async fn test_lagging_subscriber_counts_missed_events() {
+        let api = PaymentStateApi::new();
+        let mut slow = api.subscribe();
+        let total = super::super::EVENT_BROADCAST_CAPACITY as u64 + 3;
+        
+        // Alterna AwaitingInfo <-> EMVPayment
+        for _ in 0..total {
+            api.execute(AwaitingInfoAction::SetAmount { amount: 10.0 }).await.unwrap();
+            api.execute(AwaitingInfoAction::SetPaymentType { 
+                payment_type: PaymentType::Credit 
+            }).await.unwrap();
+            api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+            api.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+        }
+        
+        let mut received = 0;
+        while slow.try_recv().await.unwrap().is_some() {
+            received += 1;
+        }
+        assert_eq!(received, super::super::EVENT_BROADCAST_CAPACITY as u64);
+        assert_eq!(slow.missed_events(), total * 2 - received);
+    }
+    
+    #[tokio::test]
+    async fn test_history_records_full_payment_flow() {
+        let api = PaymentStateApi::new();
+        
+        api.execute(AwaitingInfoAction::SetAmount { amount: 90.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Credit 
+        }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        api.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN900".to_string(),
+                authorization_code: "AUTH900".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        
+        let history = api.history().await;
+        let steps: Vec<_> = history.iter().map(|e| (e.from_state, e.to_state)).collect();
+        assert_eq!(steps, vec![
+            (StateType::AwaitingInfo, StateType::EMVPayment),
+            (StateType::EMVPayment, StateType::PaymentSuccess),
+        ]);
+        for event in &history {
+            assert!(chrono::DateTime::parse_from_rfc3339(&event.timestamp).is_ok());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_history_capacity_drops_oldest() {
+        let api = PaymentStateApi::new();
+        api.set_memory_budget(DEFAULT_MAX_EVENTS, 2).await;
+        
+        for _ in 0..3 {
+            api.execute(AwaitingInfoAction::SetAmount { amount: 10.0 }).await.unwrap();
+            api.execute(AwaitingInfoAction::SetPaymentType { 
+                payment_type: PaymentType::Debit 
+            }).await.unwrap();
+            api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+            api.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+        }
+        
+        let seqs: Vec<u64> = api.history().await.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![5, 6]);
+    }
 }
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Fonte de tempo injetável para regras dependentes de tempo
+/// 
+/// Estados guardam um `Arc<dyn Clock>`; em produção `SystemClock`,
+/// em testes `ManualClock` para avançar o tempo deterministicamente.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Relógio do sistema (UTC)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Relógio usado ao restaurar um estado persistido
+pub fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Relógio controlado manualmente
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+#[allow(dead_code)]
+impl ManualClock {
+    /// Cria o relógio parado em `start`
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(start) }
+    }
+    
+    /// Avança o relógio
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
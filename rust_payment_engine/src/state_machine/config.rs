@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+// ==================== CONFIGURAÇÃO GLOBAL DO FLUXO ====================
+//
+// Valores f64 são armazenados como bits em AtomicU64 para leitura sem lock
+// a partir dos estados (que são síncronos). Nos testes cada thread tem sua
+// própria cópia: cada `#[tokio::test]` roda em uma thread, então testes que
+// alteram a configuração não interferem nos que rodam em paralelo.
+
+macro_rules! config_cell {
+    ($name:ident, $initial:expr) => {
+        #[cfg(not(test))]
+        static $name: AtomicU64 = AtomicU64::new($initial);
+        #[cfg(test)]
+        thread_local! {
+            static $name: AtomicU64 = const { AtomicU64::new($initial) };
+        }
+    };
+}
+
+#[cfg(not(test))]
+type ConfigCell = AtomicU64;
+#[cfg(test)]
+type ConfigCell = std::thread::LocalKey<AtomicU64>;
+
+#[cfg(not(test))]
+fn load(cell: &'static ConfigCell) -> u64 {
+    cell.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+fn load(cell: &'static ConfigCell) -> u64 {
+    cell.with(|value| value.load(Ordering::Relaxed))
+}
+
+#[cfg(not(test))]
+fn store(cell: &'static ConfigCell, value: u64) {
+    cell.store(value, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+fn store(cell: &'static ConfigCell, value: u64) {
+    cell.with(|cell| cell.store(value, Ordering::Relaxed))
+}
+
+/// Valor padrão acima do qual cancelamentos exigem motivo (R$)
+pub const DEFAULT_CANCEL_REASON_THRESHOLD: f64 = 1000.0;
+
+config_cell!(CANCEL_REASON_THRESHOLD, DEFAULT_CANCEL_REASON_THRESHOLD.to_bits());
+
+/// Retorna o valor acima do qual cancelamentos exigem motivo
+pub fn cancel_reason_threshold() -> f64 {
+    f64::from_bits(load(&CANCEL_REASON_THRESHOLD))
+}
+
+/// Define o valor acima do qual cancelamentos exigem motivo
+/// 
+/// Rejeita valores negativos ou não finitos (configuração anterior mantida).
+pub fn set_cancel_reason_threshold(threshold: f64) -> anyhow::Result<()> {
+    if !threshold.is_finite() || threshold < 0.0 {
+        return Err(anyhow::anyhow!("Limite de motivo de cancelamento inválido: {}", threshold));
+    }
+    store(&CANCEL_REASON_THRESHOLD, threshold.to_bits());
+    Ok(())
+}
+
+/// Menor valor aceito pelo adquirente (R$)
+pub const DEFAULT_MIN_AMOUNT: f64 = 0.01;
+
+/// Maior valor aceito pelo adquirente (R$)
+pub const DEFAULT_MAX_AMOUNT: f64 = 50_000.0;
+
+config_cell!(MIN_AMOUNT, DEFAULT_MIN_AMOUNT.to_bits());
+config_cell!(MAX_AMOUNT, DEFAULT_MAX_AMOUNT.to_bits());
+
+/// Retorna os limites (mínimo, máximo) aceitos por SetAmount
+pub fn amount_limits() -> (f64, f64) {
+    (
+        f64::from_bits(load(&MIN_AMOUNT)),
+        f64::from_bits(load(&MAX_AMOUNT)),
+    )
+}
+
+/// Define os limites aceitos por SetAmount
+/// 
+/// Rejeita valores negativos, não finitos ou `min > max` (limites
+/// anteriores mantidos).
+pub fn set_amount_limits(min: f64, max: f64) -> anyhow::Result<()> {
+    if !min.is_finite() || !max.is_finite() || min < 0.0 || min > max {
+        return Err(anyhow::anyhow!("Limites de valor inválidos: {} a {}", min, max));
+    }
+    store(&MIN_AMOUNT, min.to_bits());
+    store(&MAX_AMOUNT, max.to_bits());
+    Ok(())
+}
+
+/// Valor padrão acima do qual a aprovação exige confirmação do portador (R$)
+pub const DEFAULT_CARDHOLDER_CONFIRMATION_THRESHOLD: f64 = 5000.0;
+
+config_cell!(CARDHOLDER_CONFIRMATION_THRESHOLD, DEFAULT_CARDHOLDER_CONFIRMATION_THRESHOLD.to_bits());
+
+/// Retorna o valor acima do qual CompletePayment exige ConfirmCardholder
+pub fn cardholder_confirmation_threshold() -> f64 {
+    f64::from_bits(load(&CARDHOLDER_CONFIRMATION_THRESHOLD))
+}
+
+/// Define o valor acima do qual CompletePayment exige ConfirmCardholder
+/// 
+/// Rejeita valores negativos ou não finitos (configuração anterior mantida).
+pub fn set_cardholder_confirmation_threshold(threshold: f64) -> anyhow::Result<()> {
+    if !threshold.is_finite() || threshold < 0.0 {
+        return Err(anyhow::anyhow!("Limite de confirmação do portador inválido: {}", threshold));
+    }
+    store(&CARDHOLDER_CONFIRMATION_THRESHOLD, threshold.to_bits());
+    Ok(())
+}
+
+/// Janela padrão após o início do processamento em que cancelar ainda é seguro
+pub const DEFAULT_CANCEL_GRACE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Maior janela aceita: depois disso a autorização já pode ter sido enviada
+pub const MAX_CANCEL_GRACE_WINDOW: Duration = Duration::from_secs(30);
+
+config_cell!(CANCEL_GRACE_WINDOW_MS, DEFAULT_CANCEL_GRACE_WINDOW.as_millis() as u64);
+
+/// Retorna a janela em que CancelPayment é permitido após ProcessPayment
+pub fn cancel_grace_window() -> Duration {
+    Duration::from_millis(load(&CANCEL_GRACE_WINDOW_MS))
+}
+
+/// Define a janela em que CancelPayment é permitido após ProcessPayment
+/// 
+/// Rejeita janelas acima de `MAX_CANCEL_GRACE_WINDOW`.
+pub fn set_cancel_grace_window(window: Duration) -> anyhow::Result<()> {
+    if window > MAX_CANCEL_GRACE_WINDOW {
+        return Err(anyhow::anyhow!(
+            "Janela de cancelamento deve ser de até {}s",
+            MAX_CANCEL_GRACE_WINDOW.as_secs()
+        ));
+    }
+    store(&CANCEL_GRACE_WINDOW_MS, window.as_millis() as u64);
+    Ok(())
+}
+
+/// Tamanho máximo da chave PIX no BR Code (campo 26 limitado a 99)
+pub const MAX_PIX_KEY_LENGTH: usize = 77;
+
+/// Tamanho máximo do nome do recebedor (campo 59)
+pub const MAX_PIX_MERCHANT_NAME_LENGTH: usize = 25;
+
+/// Tamanho máximo da cidade do recebedor (campo 60)
+pub const MAX_PIX_MERCHANT_CITY_LENGTH: usize = 15;
+
+/// Recebedor do PIX: campos obrigatórios do BR Code
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixMerchant {
+    pub key: String,
+    pub name: String,
+    pub city: String,
+}
+
+static PIX_MERCHANT: RwLock<Option<PixMerchant>> = RwLock::new(None);
+
+/// Retorna o recebedor PIX configurado (None enquanto não definido)
+pub fn pix_merchant() -> Option<PixMerchant> {
+    PIX_MERCHANT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Define o recebedor PIX usado nos QR codes
+/// 
+/// Os três campos são obrigatórios, ASCII e dentro dos limites do BR Code.
+pub fn set_pix_merchant(key: &str, name: &str, city: &str) -> anyhow::Result<()> {
+    let fields = [
+        ("Chave PIX", key.trim(), MAX_PIX_KEY_LENGTH),
+        ("Nome do recebedor", name.trim(), MAX_PIX_MERCHANT_NAME_LENGTH),
+        ("Cidade do recebedor", city.trim(), MAX_PIX_MERCHANT_CITY_LENGTH),
+    ];
+    for (label, value, max) in fields {
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("{} obrigatória", label));
+        }
+        if !value.is_ascii() || value.len() > max {
+            return Err(anyhow::anyhow!("{} deve ter até {} caracteres ASCII", label, max));
+        }
+    }
+    
+    *PIX_MERCHANT.write().unwrap_or_else(|e| e.into_inner()) = Some(PixMerchant {
+        key: key.trim().to_string(),
+        name: name.trim().to_string(),
+        city: city.trim().to_string(),
+    });
+    Ok(())
+}
@@ -4,6 +4,11 @@ mod state_manager;
 pub mod types;
 mod registry;
 mod api;
+pub mod config;
+pub mod clock;
+pub mod velocity;
+pub mod schema;
+pub mod snapshot_diff;
 
 #[cfg(test)]
 mod state_manager_tests;
@@ -13,4 +18,4 @@ pub use states::*;
 pub use state_manager::*;
 pub use types::*;
 pub use registry::initialize_registry;
-pub use api::PaymentStateApi;
+pub use api::{PaymentStateApi, EventSubscriber};
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Once, OnceLock, RwLock};
 use anyhow::Result;
 use super::StateType;
 use super::state_trait::PaymentState;
+use super::schema::ActionSchema;
 
 /// Função que pode executar uma ação em um estado
 type DispatchFn = fn(
@@ -10,58 +12,239 @@ type DispatchFn = fn(
     action: Box<dyn std::any::Any>,
 ) -> Result<Option<(StateType, Box<dyn std::any::Any + Send + Sync>)>>;
 
+/// Função que informa se um estado (type-erased) é terminal
+type TerminalFn = fn(state: &(dyn std::any::Any + Send + Sync)) -> bool;
+
+/// Função que informa se a entrada no estado (type-erased) pode ser desfeita
+type ReversibleFn = fn(state: &(dyn std::any::Any + Send + Sync)) -> bool;
+
+/// Função que descreve um estado (type-erased)
+type DescriptionFn = fn(state: &(dyn std::any::Any + Send + Sync)) -> String;
+
+/// Função que serializa os dados de um estado (type-erased)
+type SerializeFn = fn(state: &(dyn std::any::Any + Send + Sync)) -> Result<serde_json::Value>;
+
+/// Função que reconstrói um estado concreto a partir de seus dados
+type DeserializeFn = fn(data: serde_json::Value) -> Result<Box<dyn std::any::Any + Send + Sync>>;
+
+/// Função que lista as ações aceitas por um tipo de estado
+type ActionsFn = fn() -> &'static [ActionSchema];
+
+/// Funções registradas para um tipo de estado
+#[derive(Clone, Copy)]
+pub struct StateEntry {
+    pub dispatch: DispatchFn,
+    pub is_terminal: TerminalFn,
+    pub is_reversible: ReversibleFn,
+    pub description: DescriptionFn,
+    pub serialize: SerializeFn,
+    pub deserialize: DeserializeFn,
+    pub actions: ActionsFn,
+}
+
 /// Registry global de estados
-static STATE_REGISTRY: OnceLock<HashMap<StateType, DispatchFn>> = OnceLock::new();
+static STATE_REGISTRY: OnceLock<RwLock<HashMap<StateType, StateEntry>>> = OnceLock::new();
+
+/// Incrementado a cada registro, para invalidar caches de entradas
+static REGISTRY_GENERATION: AtomicU64 = AtomicU64::new(0);
 
-/// Registra um estado no registry
+fn registry() -> &'static RwLock<HashMap<StateType, StateEntry>> {
+    STATE_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Registra (ou substitui) um estado no registry
+/// 
+/// Pode ser chamado a qualquer momento, antes ou depois de
+/// `initialize_registry` (registros prévios têm precedência sobre os padrões).
 #[allow(dead_code)]
-pub fn register_state(state_type: StateType, dispatch_fn: DispatchFn) {
-    STATE_REGISTRY.get_or_init(|| {
-        let mut map = HashMap::new();
-        map.insert(state_type, dispatch_fn);
-        map
-    });
+pub fn register_state(state_type: StateType, entry: StateEntry) {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry.insert(state_type, entry);
+    REGISTRY_GENERATION.fetch_add(1, Ordering::SeqCst);
 }
 
 /// Obtém a função de dispatch para um estado
+#[allow(dead_code)]
 pub fn get_dispatch_fn(state_type: StateType) -> Option<DispatchFn> {
-    STATE_REGISTRY.get().and_then(|registry| registry.get(&state_type).copied())
+    get_entry(state_type).map(|entry| entry.dispatch)
+}
+
+/// Obtém todas as funções registradas para um estado
+#[allow(dead_code)]
+pub fn get_entry(state_type: StateType) -> Option<StateEntry> {
+    get_entry_with_generation(state_type).map(|(_, entry)| entry)
+}
+
+/// Obtém a entrada junto com a geração do registry em que foi lida
+pub fn get_entry_with_generation(state_type: StateType) -> Option<(u64, StateEntry)> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    let generation = REGISTRY_GENERATION.load(Ordering::SeqCst);
+    registry.get(&state_type).map(|entry| (generation, *entry))
+}
+
+/// Geração atual do registry (muda a cada `register_state`)
+pub fn registry_generation() -> u64 {
+    REGISTRY_GENERATION.load(Ordering::SeqCst)
+}
+
+/// Executa a ação `A` no estado concreto `S`
+fn dispatch<S, A>(
+    state: &mut Box<dyn std::any::Any + Send + Sync>,
+    action: Box<dyn std::any::Any>,
+) -> Result<Option<(StateType, Box<dyn std::any::Any + Send + Sync>)>>
+where
+    S: PaymentState<A> + 'static,
+    A: 'static,
+{
+    let state = state.downcast_mut::<S>()
+        .ok_or_else(|| anyhow::anyhow!("Estado inválido"))?;
+    let action = action.downcast::<A>()
+        .map_err(|_| anyhow::anyhow!("Ação incompatível"))?;
+    state.execute_action_with_transition(*action)
+}
+
+/// Consulta `PaymentState::is_terminal` no estado concreto `S`
+fn is_terminal<S, A>(state: &(dyn std::any::Any + Send + Sync)) -> bool
+where
+    S: PaymentState<A> + 'static,
+{
+    state.downcast_ref::<S>().is_some_and(|state| state.is_terminal())
+}
+
+/// Consulta `PaymentState::is_reversible` no estado concreto `S`
+fn is_reversible<S, A>(state: &(dyn std::any::Any + Send + Sync)) -> bool
+where
+    S: PaymentState<A> + 'static,
+{
+    state.downcast_ref::<S>().is_some_and(|state| state.is_reversible())
+}
+
+/// Consulta `PaymentState::description` no estado concreto `S`
+fn description<S, A>(state: &(dyn std::any::Any + Send + Sync)) -> String
+where
+    S: PaymentState<A> + 'static,
+{
+    state.downcast_ref::<S>().map(|state| state.description()).unwrap_or_default()
+}
+
+/// Serializa o estado concreto `S`
+fn serialize<S>(state: &(dyn std::any::Any + Send + Sync)) -> Result<serde_json::Value>
+where
+    S: serde::Serialize + 'static,
+{
+    let state = state.downcast_ref::<S>()
+        .ok_or_else(|| anyhow::anyhow!("Estado inválido"))?;
+    Ok(serde_json::to_value(state)?)
+}
+
+/// Reconstrói o estado concreto `S`
+fn deserialize<S>(data: serde_json::Value) -> Result<Box<dyn std::any::Any + Send + Sync>>
+where
+    S: serde::de::DeserializeOwned + Send + Sync + 'static,
+{
+    Ok(Box::new(serde_json::from_value::<S>(data)?))
+}
+
+/// Monta a entrada do estado `S`, que aceita ações `A`
+pub fn entry_for<S, A>(actions: ActionsFn) -> StateEntry
+where
+    S: PaymentState<A> + serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    A: 'static,
+{
+    StateEntry {
+        dispatch: dispatch::<S, A>,
+        is_terminal: is_terminal::<S, A>,
+        is_reversible: is_reversible::<S, A>,
+        description: description::<S, A>,
+        serialize: serialize::<S>,
+        deserialize: deserialize::<S>,
+        actions,
+    }
 }
 
 /// Inicializa o registry com todos os estados
+/// 
+/// Idempotente: os padrões são registrados uma única vez e não sobrescrevem
+/// estados já registrados via `register_state`.
 #[allow(dead_code)]
 pub fn initialize_registry() {
+    static INIT: Once = Once::new();
+    INIT.call_once(register_default_states);
+}
+
+fn register_default_states() {
     use super::states::*;
     
     let mut registry = HashMap::new();
     
-    // AwaitingInfo
-    registry.insert(StateType::AwaitingInfo, (|state: &mut Box<dyn std::any::Any + Send + Sync>, action: Box<dyn std::any::Any>| {
-        let state = state.downcast_mut::<AwaitingInfo>()
-            .ok_or_else(|| anyhow::anyhow!("Estado inválido"))?;
-        let action = action.downcast::<AwaitingInfoAction>()
-            .map_err(|_| anyhow::anyhow!("Ação incompatível"))?;
-        state.execute_action_with_transition(*action)
-    }) as DispatchFn);
-    
-    // EMVPayment
-    registry.insert(StateType::EMVPayment, (|state: &mut Box<dyn std::any::Any + Send + Sync>, action: Box<dyn std::any::Any>| {
-        let state = state.downcast_mut::<EMVPayment>()
-            .ok_or_else(|| anyhow::anyhow!("Estado inválido"))?;
-        let action = action.downcast::<EmvPaymentAction>()
-            .map_err(|_| anyhow::anyhow!("Ação incompatível"))?;
-        state.execute_action_with_transition(*action)
-    }) as DispatchFn);
-    
-    // PaymentSuccess
-    registry.insert(StateType::PaymentSuccess, (|state: &mut Box<dyn std::any::Any + Send + Sync>, action: Box<dyn std::any::Any>| {
-        let state = state.downcast_mut::<PaymentSuccess>()
-            .ok_or_else(|| anyhow::anyhow!("Estado inválido"))?;
-        let action = action.downcast::<PaymentSuccessAction>()
-            .map_err(|_| anyhow::anyhow!("Ação incompatível"))?;
-        state.execute_action_with_transition(*action)
-    }) as DispatchFn);
+    registry.insert(StateType::AwaitingInfo, entry_for::<AwaitingInfo, AwaitingInfoAction>(|| awaiting_info::SCHEMA.actions));
+    registry.insert(StateType::EMVPayment, entry_for::<EMVPayment, EmvPaymentAction>(|| emv_payment::SCHEMA.actions));
+    registry.insert(StateType::PaymentSuccess, entry_for::<PaymentSuccess, PaymentSuccessAction>(|| payment_success::SCHEMA.actions));
+    registry.insert(StateType::PaymentFailed, entry_for::<PaymentFailed, PaymentFailedAction>(|| payment_failed::SCHEMA.actions));
+    registry.insert(StateType::CollectRemainder, entry_for::<CollectRemainder, CollectRemainderAction>(|| collect_remainder::SCHEMA.actions));
+    registry.insert(StateType::Voided, entry_for::<Voided, VoidedAction>(|| voided::SCHEMA.actions));
+    registry.insert(StateType::Declined, entry_for::<Declined, DeclinedAction>(|| declined::SCHEMA.actions));
+    registry.insert(StateType::Refund, entry_for::<Refund, RefundAction>(|| refund::SCHEMA.actions));
+    registry.insert(StateType::PixPayment, entry_for::<PixPayment, PixPaymentAction>(|| pix_payment::SCHEMA.actions));
     
-    // Inicializa o OnceLock
-    let _ = STATE_REGISTRY.set(registry);
+    // Publica no registry global sem sobrescrever registros prévios
+    let mut shared = self::registry().write().unwrap_or_else(|e| e.into_inner());
+    for (state_type, entry) in registry {
+        shared.entry(state_type).or_insert(entry);
+    }
+    REGISTRY_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use crate::state_machine::states::*;
+    use crate::state_machine::StateManager;
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::AtomicUsize;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Estado fictício registrado só depois de `initialize_registry`
+    #[derive(Debug, Serialize, Deserialize)]
+    struct DummyState;
+
+    #[derive(Debug, Clone)]
+    struct DummyAction;
+
+    impl PaymentState<DummyAction> for DummyState {
+        fn execute_action_with_transition(
+            &mut self,
+            _action: DummyAction,
+        ) -> Result<Option<(StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(Some((StateType::AwaitingInfo, Box::new(AwaitingInfo::initial()))))
+        }
+
+        fn state_type(&self) -> StateType {
+            StateType::TestDummy
+        }
+
+        fn description(&self) -> String {
+            "Estado fictício".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_state_after_initialization_takes_effect() {
+        initialize_registry();
+        
+        // Manager criado antes do registro: o cache deve ser invalidado
+        let (manager, _rx) = StateManager::new(Box::new(DummyState), StateType::TestDummy);
+        assert!(manager.execute(DummyAction).await.is_err());
+        
+        register_state(StateType::TestDummy, entry_for::<DummyState, DummyAction>(|| &[]));
+        manager.execute(DummyAction).await.unwrap();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        
+        // initialize_registry não remove registros feitos depois dele
+        initialize_registry();
+        assert!(get_entry(StateType::TestDummy).is_some());
+    }
 }
@@ -0,0 +1,82 @@
+//! Metadados da máquina de estados para ferramentas e codegen
+//!
+//! Cada estado declara seu próprio `SCHEMA` (ações, parâmetros e transições
+//! de saída) ao lado da definição de suas ações. Este módulo apenas os reúne.
+
+use serde::Serialize;
+use super::StateType;
+use super::states::{
+    awaiting_info, collect_remainder, declined, emv_payment, payment_failed,
+    payment_success, pix_payment, refund, voided,
+};
+
+/// Parâmetro de uma ação
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    /// Tipo Rust do parâmetro (ex: `f64`, `Option<String>`)
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+}
+
+/// Ação aceita por um estado
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ActionSchema {
+    /// Nome da variante (valor de `type` no JSON da ação)
+    pub name: &'static str,
+    pub params: &'static [FieldSchema],
+}
+
+/// Descrição de um estado: ações válidas e possíveis estados seguintes
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StateSchema {
+    pub state: StateType,
+    pub actions: &'static [ActionSchema],
+    pub transitions: &'static [StateType],
+}
+
+/// Schema de todos os estados, na ordem de `StateType`
+pub fn machine_schema() -> Vec<StateSchema> {
+    vec![
+        awaiting_info::SCHEMA,
+        emv_payment::SCHEMA,
+        payment_success::SCHEMA,
+        payment_failed::SCHEMA,
+        collect_remainder::SCHEMA,
+        voided::SCHEMA,
+        declined::SCHEMA,
+        refund::SCHEMA,
+        pix_payment::SCHEMA,
+    ]
+}
+
+/// Schema completo serializado como JSON
+pub fn machine_schema_json() -> String {
+    serde_json::to_string(&machine_schema()).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_schema_covers_every_state_once() {
+        let states: Vec<StateType> = machine_schema().iter().map(|s| s.state).collect();
+        let unique: HashSet<_> = states.iter().collect();
+        assert_eq!(states.len(), 9);
+        assert_eq!(unique.len(), states.len());
+    }
+
+    #[test]
+    fn test_schema_transitions_target_known_states() {
+        let schema = machine_schema();
+        let known: HashSet<StateType> = schema.iter().map(|s| s.state).collect();
+        for state in &schema {
+            assert!(!state.actions.is_empty(), "{:?} sem ações", state.state);
+            for target in state.transitions {
+                assert!(known.contains(target), "{:?} -> {:?}", state.state, target);
+            }
+        }
+    }
+}
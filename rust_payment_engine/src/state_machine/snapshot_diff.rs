@@ -0,0 +1,133 @@
+//! Comparação campo a campo entre dois snapshots do fluxo
+//!
+//! Ferramenta de suporte: mostra o que mudou entre duas leituras de
+//! `snapshot()` (troca de estado, valor, flags de processamento...).
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::PersistedState;
+
+/// Diferença em um campo, identificado pelo caminho (ex: `payment_info.amount`)
+///
+/// `before`/`after` ausentes indicam campo adicionado/removido.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Lista as diferenças de `a` para `b`
+///
+/// A troca de tipo de estado vem primeiro (caminho `state_type`); os dados
+/// do estado são comparados recursivamente, em ordem alfabética de campo.
+pub fn diff_snapshots(a: &PersistedState, b: &PersistedState) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    if a.state_type != b.state_type {
+        diffs.push(FieldDiff {
+            path: "state_type".to_string(),
+            before: Some(serde_json::json!(a.state_type)),
+            after: Some(serde_json::json!(b.state_type)),
+        });
+    }
+    diff_values("", &a.state, &b.state, &mut diffs);
+    diffs
+}
+
+/// `diff_snapshots` sobre o JSON retornado por `snapshot()`
+pub fn diff_snapshot_json(a: &str, b: &str) -> Result<Vec<FieldDiff>> {
+    let a: PersistedState = serde_json::from_str(a)?;
+    let b: PersistedState = serde_json::from_str(b)?;
+    Ok(diff_snapshots(&a, &b))
+}
+
+fn diff_values(path: &str, a: &Value, b: &Value, diffs: &mut Vec<FieldDiff>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_values(&child, a, b, diffs),
+                    (before, after) => diffs.push(FieldDiff {
+                        path: child,
+                        before: before.cloned(),
+                        after: after.cloned(),
+                    }),
+                }
+            }
+        }
+        _ if a != b => diffs.push(FieldDiff {
+            path: path.to_string(),
+            before: Some(a.clone()),
+            after: Some(b.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod snapshot_diff_tests {
+    use super::*;
+    use crate::state_machine::{AwaitingInfoAction, EmvPaymentAction, PaymentStateApi, PaymentType};
+    use serde_json::json;
+
+    fn find<'a>(diffs: &'a [FieldDiff], path: &str) -> &'a FieldDiff {
+        diffs
+            .iter()
+            .find(|d| d.path == path)
+            .unwrap_or_else(|| panic!("diferença em {} ausente: {:?}", path, diffs))
+    }
+
+    #[tokio::test]
+    async fn test_diff_awaiting_info_against_emv_payment() {
+        let api = PaymentStateApi::new();
+        api.execute(AwaitingInfoAction::SetAmount { amount: 100.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Credit }).await.unwrap();
+        let before = api.snapshot().await.unwrap();
+
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        let after = api.snapshot().await.unwrap();
+
+        let diffs = diff_snapshot_json(&before, &after).unwrap();
+
+        assert_eq!(diffs[0], FieldDiff {
+            path: "state_type".to_string(),
+            before: Some(json!("AwaitingInfo")),
+            after: Some(json!("EMVPayment")),
+        });
+        let amount = find(&diffs, "amount");
+        assert_eq!(amount.before, Some(json!(100.0)));
+        assert_eq!(amount.after, None);
+        let payment_info = find(&diffs, "payment_info");
+        assert_eq!(payment_info.before, None);
+        assert_eq!(payment_info.after.as_ref().unwrap()["amount"], json!(100.0));
+        // Campo comum aos dois estados e inalterado não aparece
+        assert!(diffs.iter().all(|d| d.path != "require_processing"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_within_same_state_reports_nested_fields() {
+        let api = PaymentStateApi::new();
+        api.execute(AwaitingInfoAction::SetAmount { amount: 100.0 }).await.unwrap();
+        api.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        api.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        let before = api.snapshot().await.unwrap();
+
+        api.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let after = api.snapshot().await.unwrap();
+
+        let diffs = diff_snapshot_json(&before, &after).unwrap();
+        assert!(diffs.iter().all(|d| d.path != "state_type"));
+        let processing = find(&diffs, "processing");
+        assert_eq!(processing.before, Some(json!(false)));
+        assert_eq!(processing.after, Some(json!(true)));
+
+        assert!(diff_snapshot_json(&before, &before).unwrap().is_empty());
+        assert!(diff_snapshot_json("{}", &after).is_err());
+    }
+}
@@ -1,9 +1,83 @@
 use anyhow::Result;
-use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
-use super::{StateType, StateChangeEvent};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc};
+use tokio::time::Instant;
+use super::{StateType, StateChangeEvent, StateEvent, PersistedState, TransitionOutcome, TransitionRecord};
+use super::registry::StateEntry;
 
 
+/// Limite padrão de eventos retidos para replay
+pub const DEFAULT_MAX_EVENTS: usize = 256;
+
+/// Limite padrão de registros na linha do tempo de transições
+pub const DEFAULT_MAX_HISTORY: usize = 100;
+
+/// Limite padrão de estados anteriores guardados para undo
+pub const DEFAULT_MAX_UNDO: usize = 10;
+
+/// Eventos retidos por assinante do broadcast antes de sinalizar atraso
+pub const EVENT_BROADCAST_CAPACITY: usize = 64;
+
+/// Orçamento de memória dos buffers de longa duração
+/// 
+/// Ao exceder um limite, as entradas mais antigas são descartadas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+    /// Máximo de eventos no buffer de replay
+    pub max_events: usize,
+    /// Máximo de registros no histórico e na linha do tempo de transições
+    pub max_history: usize,
+    /// Máximo de estados anteriores na pilha de undo
+    pub max_undo: usize,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self {
+            max_events: DEFAULT_MAX_EVENTS,
+            max_history: DEFAULT_MAX_HISTORY,
+            max_undo: DEFAULT_MAX_UNDO,
+        }
+    }
+}
+
+/// Efeito colateral de entrada/saída de estado (ex: ligar o leitor NFC)
+pub type StateHook = Arc<dyn Fn() + Send + Sync>;
+
+/// Fila única dos hooks, consumida por uma thread dedicada
+/// 
+/// Os lotes são enfileirados com a transição serializada pelo lock de
+/// estado, então a thread os executa na ordem das transições sem atrasar
+/// a máquina.
+fn hook_queue() -> &'static std::sync::mpsc::Sender<Vec<StateHook>> {
+    static QUEUE: OnceLock<std::sync::mpsc::Sender<Vec<StateHook>>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (tx, rx) = std::sync::mpsc::channel::<Vec<StateHook>>();
+        // Sem a thread os envios falham e os hooks são descartados
+        let _ = std::thread::Builder::new()
+            .name("state-hooks".to_string())
+            .spawn(move || {
+                for hook in rx.into_iter().flatten() {
+                    // Um hook com panic não derruba a fila
+                    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook()));
+                }
+            });
+        tx
+    })
+}
+
+/// Hooks registrados por estado
+#[derive(Default)]
+struct StateHooks {
+    on_enter: HashMap<StateType, Vec<StateHook>>,
+    on_exit: HashMap<StateType, Vec<StateHook>>,
+}
+
+/// Entrada do registry em cache: (tipo, geração do registry, entrada)
+type CachedEntry = (StateType, u64, StateEntry);
+
 /// ===============================================================================
 /// STATEMANAGER 100% GENÉRICO - ZERO LÓGICA DE ESTADOS
 /// ===============================================================================
@@ -27,8 +101,44 @@ pub struct StateManager {
     /// Tipo do estado atual (para notificações)
     current_state_type: Arc<RwLock<StateType>>,
     
+    /// Entrada do registry do estado atual (evita a busca no mapa a cada ação)
+    cached_entry: Arc<RwLock<Option<CachedEntry>>>,
+    
     /// Canal para notificar Flutter
     state_sender: mpsc::UnboundedSender<StateChangeEvent>,
+    
+    /// Fan-out para assinantes adicionais (ex: logger de auditoria)
+    event_broadcast: broadcast::Sender<StateChangeEvent>,
+    
+    /// Canal opcional de eventos completos (mudanças e falhas)
+    state_events: Arc<std::sync::Mutex<Option<mpsc::UnboundedSender<StateEvent>>>>,
+    
+    /// Próximo número sequencial de evento
+    next_seq: Arc<AtomicU64>,
+    
+    /// Eventos marcados como obsoletos que ainda não foram entregues
+    /// 
+    /// Limitado aos eventos retidos para replay e ainda não entregues
+    /// por `resolve_superseded`.
+    superseded: Arc<Mutex<HashSet<u64>>>,
+    
+    /// Transições emitidas, do mais antigo ao mais recente
+    /// 
+    /// Fonte única da linha do tempo e do histórico (últimas `max_history`)
+    /// e do replay (últimas `max_events`); retém o maior dos dois limites.
+    transitions: Arc<RwLock<VecDeque<RecordedTransition>>>,
+    
+    /// Limites dos buffers
+    budget: Arc<RwLock<MemoryBudget>>,
+    
+    /// Estados anteriores às transições, serializados pelo registry (limitado por `max_undo`)
+    undo_stack: Arc<Mutex<VecDeque<PersistedState>>>,
+    
+    /// Instante em que o estado atual foi iniciado
+    state_entered_at: Arc<RwLock<Instant>>,
+    
+    /// Hooks de entrada/saída por estado
+    hooks: Arc<std::sync::RwLock<StateHooks>>,
 }
 
 impl Clone for StateManager {
@@ -36,7 +146,17 @@ impl Clone for StateManager {
         Self {
             current_state: Arc::clone(&self.current_state),
             current_state_type: Arc::clone(&self.current_state_type),
+            cached_entry: Arc::clone(&self.cached_entry),
             state_sender: self.state_sender.clone(),
+            event_broadcast: self.event_broadcast.clone(),
+            state_events: Arc::clone(&self.state_events),
+            next_seq: Arc::clone(&self.next_seq),
+            superseded: Arc::clone(&self.superseded),
+            transitions: Arc::clone(&self.transitions),
+            budget: Arc::clone(&self.budget),
+            undo_stack: Arc::clone(&self.undo_stack),
+            state_entered_at: Arc::clone(&self.state_entered_at),
+            hooks: Arc::clone(&self.hooks),
         }
     }
 }
@@ -47,13 +167,35 @@ impl StateManager {
     pub fn new(
         initial_state: Box<dyn std::any::Any + Send + Sync>,
         initial_type: StateType,
+    ) -> (Self, mpsc::UnboundedReceiver<StateChangeEvent>) {
+        Self::with_memory_budget(initial_state, initial_type, MemoryBudget::default())
+    }
+    
+    /// Cria novo StateManager com limites de buffer próprios
+    pub fn with_memory_budget(
+        initial_state: Box<dyn std::any::Any + Send + Sync>,
+        initial_type: StateType,
+        budget: MemoryBudget,
     ) -> (Self, mpsc::UnboundedReceiver<StateChangeEvent>) {
         let (tx, rx) = mpsc::unbounded_channel();
         
         let manager = Self {
             current_state: Arc::new(RwLock::new(initial_state)),
             current_state_type: Arc::new(RwLock::new(initial_type)),
+            cached_entry: Arc::new(RwLock::new(
+                super::registry::get_entry_with_generation(initial_type)
+                    .map(|(generation, entry)| (initial_type, generation, entry)),
+            )),
             state_sender: tx,
+            event_broadcast: broadcast::channel(EVENT_BROADCAST_CAPACITY).0,
+            state_events: Arc::new(std::sync::Mutex::new(None)),
+            next_seq: Arc::new(AtomicU64::new(1)),
+            superseded: Arc::new(Mutex::new(HashSet::new())),
+            transitions: Arc::new(RwLock::new(VecDeque::new())),
+            budget: Arc::new(RwLock::new(budget)),
+            undo_stack: Arc::new(Mutex::new(VecDeque::new())),
+            state_entered_at: Arc::new(RwLock::new(Instant::now())),
+            hooks: Arc::new(std::sync::RwLock::new(StateHooks::default())),
         };
         
         (manager, rx)
@@ -70,38 +212,144 @@ impl StateManager {
     /// Se a ação não for compatível com o estado atual, retorna erro.
     /// 
     /// TOTALMENTE GENÉRICO - Não conhece nenhum estado específico!
-    pub async fn execute<A>(&self, action: A) -> Result<String>
+    /// 
+    /// Em caso de erro, publica `StateEvent::Failed` antes de retornar.
+    pub async fn execute<A>(&self, action: A) -> Result<TransitionOutcome>
     where
-        A: 'static,
+        A: 'static + Clone + std::fmt::Debug,
     {
-        // Descobre qual é o estado atual
+        // A ação é consumida pelo estado; a cópia só é formatada se falhar
+        let failed_action = action.clone();
+        
+        // Trava o estado ANTES de ler o tipo: toda escrita do tipo ocorre sob
+        // esta trava, então tipo e estado permanecem consistentes até o fim
+        let mut state_guard = self.current_state.write().await;
         let current_type = *self.current_state_type.read().await;
         
-        // Busca a função de dispatch no registry
-        let dispatch_fn = super::registry::get_dispatch_fn(current_type)
-            .ok_or_else(|| anyhow::anyhow!("Estado não registrado: {:?}", current_type))?;
+        let result = self.dispatch_action(&mut state_guard, current_type, action).await;
         
-        let mut state_guard = self.current_state.write().await;
+        if let Err(error) = &result {
+            self.publish_state_event(StateEvent::Failed {
+                state: current_type,
+                action: format!("{:?}", failed_action),
+                error: error.to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+        
+        result
+    }
+    
+    /// Ativa o canal de `StateEvent` e retorna seu receptor
+    /// 
+    /// Um novo assinante substitui o anterior. Enquanto ninguém assinar,
+    /// nenhum `StateEvent` é produzido.
+    pub fn subscribe_state_events(&self) -> mpsc::UnboundedReceiver<StateEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.state_events.lock().unwrap_or_else(|e| e.into_inner()) = Some(tx);
+        rx
+    }
+    
+    /// Executa a ação no estado atual e aplica a transição, se houver
+    /// 
+    /// Chamado com a trava de escrita do estado já adquirida.
+    async fn dispatch_action<A>(
+        &self,
+        state_guard: &mut Box<dyn std::any::Any + Send + Sync>,
+        current_type: StateType,
+        action: A,
+    ) -> Result<TransitionOutcome>
+    where
+        A: 'static,
+    {
+        // Busca a função de dispatch (cache, com fallback para o registry)
+        let entry = self.entry_for(current_type).await?;
         let action_boxed = Box::new(action) as Box<dyn std::any::Any>;
         
         // Executa usando a função registrada
-        let transition = dispatch_fn(&mut *state_guard, action_boxed)?;
+        let transition = (entry.dispatch)(state_guard, action_boxed)?;
         
         // Se houver transição, SUBSTITUI estado
         if let Some((new_type, new_state)) = transition {
-            // Captura o tipo do estado ANTES de modificar
-            let old_type = *self.current_state_type.read().await;
+            let old_type = current_type;
             
-            *state_guard = new_state;
+            let old_state = std::mem::replace(state_guard, new_state);
             *self.current_state_type.write().await = new_type;
+            // Estados terminais ou irreversíveis (ex: autorização em curso) nunca
+            // voltam, e voltar além deles pularia um passo do fluxo
+            if (entry.is_reversible)(&*old_state) && !(entry.is_terminal)(&*old_state) {
+                self.push_undo(old_type, (entry.serialize)(&*old_state)).await;
+            } else {
+                self.undo_stack.lock().await.clear();
+            }
+            let new_entry = self.refresh_cached_entry(new_type).await;
+            // Um fluxo encerrado não pode ser percorrido de volta
+            if new_entry.is_some_and(|entry| (entry.is_terminal)(&**state_guard)) {
+                self.undo_stack.lock().await.clear();
+            }
             
-            // Notifica Flutter com o estado correto
-            self.notify_state_change(old_type, new_type).await?;
+            self.emit_transition(old_type, new_type).await;
             
-            Ok(format!("Transicionado para {:?}", new_type))
+            Ok(TransitionOutcome { transitioned: true, new_state: new_type, previous_state: old_type })
         } else {
-            Ok("Ação executada - permanece no mesmo estado".to_string())
+            Ok(TransitionOutcome { transitioned: false, new_state: current_type, previous_state: current_type })
+        }
+    }
+    
+    /// Guarda o estado anterior a uma transição na pilha de undo
+    /// 
+    /// Se o estado não puder ser serializado a pilha é esvaziada: voltar
+    /// além dele pularia um passo do fluxo.
+    async fn push_undo(&self, state_type: StateType, state: Result<serde_json::Value>) {
+        let max_undo = self.budget.read().await.max_undo;
+        let mut stack = self.undo_stack.lock().await;
+        match state {
+            Ok(state) => {
+                stack.push_back(PersistedState { state_type, state });
+                trim_front(&mut stack, max_undo);
+            }
+            Err(_) => stack.clear(),
+        }
+    }
+    
+    /// Desfaz a última transição, restaurando o estado anterior
+    /// 
+    /// Só é permitido se tanto o estado atual quanto o restaurado forem
+    /// reversíveis (`PaymentState::is_reversible`) e o restaurado não for
+    /// terminal: uma venda concluída nunca é reaberta. Emite um `StateChangeEvent` no sentido
+    /// inverso. Chamadas sucessivas voltam até `max_undo` transições; o estado
+    /// anterior é recriado pelo `deserialize` do registry.
+    pub async fn undo(&self) -> Result<String> {
+        let mut state_guard = self.current_state.write().await;
+        let current_type = *self.current_state_type.read().await;
+        
+        let reversible = self.entry_for(current_type).await
+            .is_ok_and(|entry| (entry.is_reversible)(&**state_guard));
+        if !reversible {
+            return Err(anyhow::anyhow!("Transição para {:?} não pode ser desfeita", current_type));
+        }
+        
+        let mut stack = self.undo_stack.lock().await;
+        let previous = stack
+            .back()
+            .ok_or_else(|| anyhow::anyhow!("Nenhuma transição para desfazer"))?;
+        let previous_type = previous.state_type;
+        let entry = super::registry::get_entry(previous_type)
+            .ok_or_else(|| anyhow::anyhow!("Estado não registrado: {:?}", previous_type))?;
+        let previous_state = (entry.deserialize)(previous.state.clone())?;
+        if !(entry.is_reversible)(&*previous_state) || (entry.is_terminal)(&*previous_state) {
+            return Err(anyhow::anyhow!("Não é possível voltar para {:?}", previous_type));
         }
+        stack.pop_back();
+        drop(stack);
+        
+        *state_guard = previous_state;
+        *self.current_state_type.write().await = previous_type;
+        self.refresh_cached_entry(previous_type).await;
+        
+        self.emit_transition(current_type, previous_type).await;
+        
+        Ok(format!("Desfeito: retornou para {:?}", previous_type))
     }
     
     /// Retorna o tipo do estado atual
@@ -109,11 +357,102 @@ impl StateManager {
         *self.current_state_type.read().await
     }
     
+    /// Indica se o estado atual é terminal (consulta o registry)
+    pub async fn is_terminal(&self) -> bool {
+        let state_guard = self.current_state.read().await;
+        let current_type = *self.current_state_type.read().await;
+        
+        self.entry_for(current_type).await
+            .is_ok_and(|entry| (entry.is_terminal)(&**state_guard))
+    }
+    
+    /// Descrição do estado atual, sem conhecer seu tipo concreto
+    pub async fn current_description(&self) -> Result<String> {
+        let state_guard = self.current_state.read().await;
+        let current_type = *self.current_state_type.read().await;
+        
+        let entry = self.entry_for(current_type).await?;
+        Ok((entry.description)(&**state_guard))
+    }
+    
+    /// Nomes das ações aceitas no estado atual (consulta o registry)
+    pub async fn available_actions(&self) -> Result<Vec<String>> {
+        let current_type = *self.current_state_type.read().await;
+        let entry = self.entry_for(current_type).await?;
+        Ok((entry.actions)().iter().map(|action| action.name.to_string()).collect())
+    }
+    
+    /// Serializa o estado atual (tipo + dados) como JSON
+    /// 
+    /// Usado para retomar um pagamento se o app for encerrado no meio do fluxo.
+    pub async fn snapshot(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self.persisted_state().await?)?)
+    }
+    
+    /// Estado atual serializado pela entrada do registry
+    pub async fn persisted_state(&self) -> Result<PersistedState> {
+        let state_guard = self.current_state.read().await;
+        let state_type = *self.current_state_type.read().await;
+        
+        let entry = self.entry_for(state_type).await?;
+        Ok(PersistedState {
+            state_type,
+            state: (entry.serialize)(&**state_guard)?,
+        })
+    }
+    
+    /// Reconstrói um StateManager a partir de `snapshot`
+    /// 
+    /// O estado concreto é recriado pela entrada do registry do tipo salvo;
+    /// buffers (histórico, linha do tempo, undo) começam vazios.
+    pub fn restore(json: &str) -> Result<(Self, mpsc::UnboundedReceiver<StateChangeEvent>)> {
+        let persisted: PersistedState = serde_json::from_str(json)?;
+        let entry = super::registry::get_entry(persisted.state_type)
+            .ok_or_else(|| anyhow::anyhow!("Estado não registrado: {:?}", persisted.state_type))?;
+        let state = (entry.deserialize)(persisted.state)?;
+        Ok(Self::new(state, persisted.state_type))
+    }
+    
+    /// Tipo de estado cuja entrada do registry está em cache
+    pub async fn cached_entry_type(&self) -> Option<StateType> {
+        self.cached_entry.read().await.map(|(state_type, _, _)| state_type)
+    }
+    
+    /// Entrada do registry para `state_type`, preferindo o cache
+    /// 
+    /// O cache é indexado pelo tipo e pela geração do registry, então uma
+    /// entrada obsoleta (ex: estado re-registrado) nunca é usada.
+    async fn entry_for(&self, state_type: StateType) -> Result<StateEntry> {
+        if let Some((cached_type, generation, entry)) = *self.cached_entry.read().await {
+            if cached_type == state_type && generation == super::registry::registry_generation() {
+                return Ok(entry);
+            }
+        }
+        self.refresh_cached_entry(state_type).await
+            .ok_or_else(|| anyhow::anyhow!("Estado não registrado: {:?}", state_type))
+    }
+    
+    /// Recarrega o cache a partir do registry (chamado a cada transição)
+    async fn refresh_cached_entry(&self, state_type: StateType) -> Option<StateEntry> {
+        let entry = super::registry::get_entry_with_generation(state_type);
+        *self.cached_entry.write().await = entry.map(|(generation, entry)| (state_type, generation, entry));
+        entry.map(|(_, entry)| entry)
+    }
+    
     /// Retorna descrição do estado (se implementado)
     pub async fn get_description<S, F>(&self, getter: F) -> Result<String>
     where
         S: 'static + Send + Sync,
         F: FnOnce(&S) -> String,
+    {
+        self.inspect::<S, _, _>(getter).await
+    }
+    
+    /// Lê um valor do estado atual, se ele for do tipo concreto `S`
+    pub async fn inspect<S, R, F>(&self, getter: F) -> Result<R>
+    where
+        S: 'static + Send + Sync,
+        F: FnOnce(&S) -> R,
     {
         let state_guard = self.current_state.read().await;
         let state = state_guard
@@ -123,18 +462,207 @@ impl StateManager {
         Ok(getter(state))
     }
     
-    /// Notifica Flutter sobre mudança de estado
-    async fn notify_state_change(&self, from_state: StateType, to_state: StateType) -> Result<()> {
+    /// Número sequencial do último evento emitido (0 se nenhum)
+    pub fn last_event_seq(&self) -> u64 {
+        self.next_seq.load(Ordering::SeqCst) - 1
+    }
+    
+    /// Marca um evento já enviado como obsoleto
+    /// 
+    /// Eventos no canal não podem ser alterados; a marca é aplicada
+    /// na entrega via `resolve_superseded`. Seqs ainda não emitidos ou já
+    /// descartados do buffer de replay são ignorados.
+    pub async fn mark_superseded(&self, seq: u64) {
+        let oldest_retained = self.recent_events().await.first().map(|event| event.seq);
+        let retained = oldest_retained.is_some_and(|oldest| seq >= oldest);
+        if retained && seq <= self.last_event_seq() {
+            self.superseded.lock().await.insert(seq);
+        }
+    }
+    
+    /// Novo assinante independente dos eventos de mudança de estado
+    /// 
+    /// Recebe apenas eventos emitidos após a assinatura. Um assinante que
+    /// fica mais de `EVENT_BROADCAST_CAPACITY` eventos atrás perde os mais
+    /// antigos (`RecvError::Lagged`).
+    pub fn subscribe(&self) -> broadcast::Receiver<StateChangeEvent> {
+        self.event_broadcast.subscribe()
+    }
+    
+    /// Como `resolve_superseded`, mas sem consumir a marca
+    /// 
+    /// Usado pelos assinantes do broadcast, que recebem o mesmo evento
+    /// que `next_event`.
+    pub async fn peek_superseded(&self, mut event: StateChangeEvent) -> StateChangeEvent {
+        if self.superseded.lock().await.contains(&event.seq) {
+            event.superseded = true;
+        }
+        event
+    }
+    
+    /// Aplica a marca de obsolescência a um evento recebido do canal
+    /// 
+    /// O canal entrega em ordem, então marcas de seqs anteriores não serão
+    /// mais consultadas e são descartadas.
+    pub async fn resolve_superseded(&self, mut event: StateChangeEvent) -> StateChangeEvent {
+        let mut superseded = self.superseded.lock().await;
+        if superseded.remove(&event.seq) {
+            event.superseded = true;
+        }
+        superseded.retain(|seq| *seq > event.seq);
+        event
+    }
+    
+    /// Retorna a linha do tempo das transições, em ordem
+    pub async fn timeline(&self) -> Vec<TransitionRecord> {
+        let max_history = self.budget.read().await.max_history;
+        let transitions = self.transitions.read().await;
+        tail(&transitions, max_history)
+            .map(|recorded| TransitionRecord {
+                from: recorded.event.from_state,
+                to: recorded.event.to_state,
+                at: recorded.event.timestamp.clone(),
+                duration_ms: recorded.duration_ms,
+            })
+            .collect()
+    }
+    
+    /// Retorna o histórico de transições, do mais antigo ao mais recente
+    pub async fn history(&self) -> Vec<StateChangeEvent> {
+        let max_history = self.budget.read().await.max_history;
+        self.events_tail(max_history).await
+    }
+    
+    /// Retorna os últimos eventos emitidos, do mais antigo ao mais recente
+    pub async fn recent_events(&self) -> Vec<StateChangeEvent> {
+        let max_events = self.budget.read().await.max_events;
+        self.events_tail(max_events).await
+    }
+    
+    async fn events_tail(&self, max: usize) -> Vec<StateChangeEvent> {
+        let transitions = self.transitions.read().await;
+        tail(&transitions, max).map(|recorded| recorded.event.clone()).collect()
+    }
+    
+    /// Define os limites dos buffers, descartando imediatamente o excedente
+    pub async fn set_memory_budget(&self, budget: MemoryBudget) {
+        *self.budget.write().await = budget;
+        trim_front(&mut *self.transitions.write().await, budget.max_events.max(budget.max_history));
+        self.prune_superseded().await;
+        trim_front(&mut *self.undo_stack.lock().await, budget.max_undo);
+    }
+    
+    /// Limites atuais dos buffers
+    pub async fn memory_budget(&self) -> MemoryBudget {
+        *self.budget.read().await
+    }
+    
+    /// Registra e notifica uma transição já aplicada
+    /// 
+    /// Não falha: a transição já foi aplicada, então um canal sem receptor
+    /// não pode transformá-la em erro da ação.
+    async fn emit_transition(&self, from: StateType, to: StateType) {
         let event = StateChangeEvent {
-            from_state,
-            to_state,
+            from_state: from,
+            to_state: to,
             timestamp: chrono::Utc::now().to_rfc3339(),
+            seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+            superseded: false,
         };
+        self.record_transition(&event).await;
+        self.run_hooks(from, to);
+        
+        // Notifica Flutter com o estado correto
+        self.notify_state_change(event);
+    }
+    
+    /// Registra um hook executado sempre que a máquina entra em `state`
+    pub fn on_enter(&self, state: StateType, hook: StateHook) {
+        let mut hooks = self.hooks.write().unwrap_or_else(|e| e.into_inner());
+        hooks.on_enter.entry(state).or_default().push(hook);
+    }
+    
+    /// Registra um hook executado sempre que a máquina sai de `state`
+    pub fn on_exit(&self, state: StateType, hook: StateHook) {
+        let mut hooks = self.hooks.write().unwrap_or_else(|e| e.into_inner());
+        hooks.on_exit.entry(state).or_default().push(hook);
+    }
+    
+    /// Dispara os hooks de saída de `from` e depois os de entrada em `to`
+    /// 
+    /// Rodam fora da transição, na thread de `hook_queue`: um hook lento
+    /// (ex: I/O com o leitor) não atrasa a máquina, e a ordem vale também
+    /// entre transições consecutivas.
+    fn run_hooks(&self, from: StateType, to: StateType) {
+        let hooks = self.hooks.read().unwrap_or_else(|e| e.into_inner());
+        let pending: Vec<StateHook> = hooks.on_exit.get(&from).into_iter().flatten()
+            .chain(hooks.on_enter.get(&to).into_iter().flatten())
+            .cloned()
+            .collect();
+        drop(hooks);
         
-        self.state_sender
-            .send(event)
-            .map_err(|e| anyhow::anyhow!("Falha ao notificar mudança de estado: {}", e))?;
+        if !pending.is_empty() {
+            let _ = hook_queue().send(pending);
+        }
+    }
+    
+    /// Registra a transição com a duração do estado anterior
+    async fn record_transition(&self, event: &StateChangeEvent) {
+        let now = Instant::now();
+        let entered_at = std::mem::replace(&mut *self.state_entered_at.write().await, now);
+        let budget = *self.budget.read().await;
         
-        Ok(())
+        let mut transitions = self.transitions.write().await;
+        transitions.push_back(RecordedTransition {
+            event: event.clone(),
+            duration_ms: now.duration_since(entered_at).as_millis() as u64,
+        });
+        trim_front(&mut transitions, budget.max_events.max(budget.max_history));
+        drop(transitions);
+        self.prune_superseded().await;
+    }
+    
+    /// Descarta marcas de eventos que já saíram do buffer de replay
+    async fn prune_superseded(&self) {
+        let oldest_retained = self.recent_events().await.first().map_or(u64::MAX, |event| event.seq);
+        self.superseded.lock().await.retain(|seq| *seq >= oldest_retained);
+    }
+    
+    /// Notifica Flutter sobre mudança de estado
+    fn notify_state_change(&self, event: StateChangeEvent) {
+        self.publish_state_event(StateEvent::Changed(event.clone()));
+        // Sem assinantes o envio falha; não é erro
+        let _ = self.event_broadcast.send(event.clone());
+        // Receptor descartado (ex: Flutter fechou o stream): o evento
+        // continua disponível em `recent_events`
+        let _ = self.state_sender.send(event);
+    }
+    
+    /// Publica no canal de `StateEvent`, se houver assinante
+    fn publish_state_event(&self, event: StateEvent) {
+        let mut sender = self.state_events.lock().unwrap_or_else(|e| e.into_inner());
+        // Assinante descartado: desativa o canal
+        if sender.as_ref().is_some_and(|tx| tx.send(event).is_err()) {
+            *sender = None;
+        }
+    }
+}
+
+/// Transição registrada com o tempo de permanência no estado anterior
+#[derive(Debug, Clone)]
+struct RecordedTransition {
+    event: StateChangeEvent,
+    duration_ms: u64,
+}
+
+/// Últimas `max` entradas de `buffer`, em ordem
+fn tail<T>(buffer: &VecDeque<T>, max: usize) -> impl Iterator<Item = &T> {
+    buffer.iter().skip(buffer.len().saturating_sub(max))
+}
+
+/// Descarta as entradas mais antigas até caber em `max`
+fn trim_front<T>(buffer: &mut VecDeque<T>, max: usize) {
+    while buffer.len() > max {
+        buffer.pop_front();
     }
 }
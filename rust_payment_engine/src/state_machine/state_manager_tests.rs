@@ -1,11 +1,22 @@
 #[cfg(test)]
 mod state_manager_tests {
-    use crate::state_machine::state_manager::StateManager;
-    use crate::state_machine::{StateType, StateChangeEvent, initialize_registry};
+    use crate::state_machine::state_manager::{StateManager, StateHook, MemoryBudget, DEFAULT_MAX_UNDO};
+    use crate::state_machine::{StateType, StateChangeEvent, StateEvent, TransitionOutcome, initialize_registry};
     use crate::state_machine::{
         AwaitingInfo, AwaitingInfoAction, PaymentType, PaymentInfo,
         EMVPayment, EmvPaymentAction, EmvResult,
+        CollectRemainder, CollectRemainderAction, PaymentSuccess,
+        PaymentFailed, FailureReason, Voided, Declined, DeclinedAction,
+        PaymentSuccessAction, Refund, RefundAction,
+        PixPayment, PixPaymentAction,
     };
+    use crate::state_machine::states::emv_payment::MAX_CHIP_READ_ATTEMPTS;
+    use crate::state_machine::states::declined::DeclinedReason;
+    use crate::state_machine::states::payment_success::{ApprovalMode, SplitTender, TipAdjustment, TIP_ADJUSTMENT_WINDOW_HOURS};
+    use crate::state_machine::clock::{default_clock, Clock, ManualClock};
+    use crate::state_machine::config::{pix_merchant, set_pix_merchant};
+    use crate::money::Amount;
+    use std::sync::Arc;
     use crate::state_machine::state_trait::PaymentState;
     use tokio::time::{timeout, Duration};
     
@@ -14,6 +25,7 @@ mod state_manager_tests {
         static INIT: std::sync::Once = std::sync::Once::new();
         INIT.call_once(|| {
             initialize_registry();
+            set_pix_merchant("loja@example.com", "LOJA EXEMPLO", "SAO PAULO").unwrap();
         });
     }
 
@@ -22,10 +34,7 @@ mod state_manager_tests {
     /// Cria um StateManager com estado inicial AwaitingInfo
     fn create_awaiting_info_manager() -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>) {
         setup();
-        let initial_state = AwaitingInfo {
-            amount: None,
-            payment_type: None,
-        };
+        let initial_state = AwaitingInfo::initial();
         
         StateManager::new(
             Box::new(initial_state),
@@ -37,15 +46,12 @@ mod state_manager_tests {
     fn create_emv_payment_manager(amount: f64, payment_type: PaymentType) -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>) {
         setup();
         let payment_info = PaymentInfo {
-            amount,
+            amount: Amount::new(amount).unwrap(),
             payment_type,
+            tip: Amount::ZERO,
         };
         
-        let emv_state = EMVPayment {
-            payment_info,
-            processing: false,
-            emv_result: None,
-        };
+        let emv_state = EMVPayment::new(payment_info);
         
         StateManager::new(
             Box::new(emv_state),
@@ -79,9 +85,10 @@ mod state_manager_tests {
         
         let action = AwaitingInfoAction::SetAmount { amount: 100.0 };
         
-        let result = manager.execute(action).await;
+        let outcome = manager.execute(action).await.unwrap();
         
-        assert!(result.is_ok());
+        assert!(!outcome.transitioned);
+        assert_eq!(outcome.new_state, StateType::AwaitingInfo);
         assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
         
         // Não deve emitir evento de mudança de estado
@@ -194,11 +201,13 @@ mod state_manager_tests {
         ).await;
         
         // Confirma e transiciona
-        let result = manager.execute(
+        let outcome = manager.execute(
             AwaitingInfoAction::ConfirmInfo
-        ).await;
+        ).await.unwrap();
         
-        assert!(result.is_ok());
+        assert!(outcome.transitioned);
+        assert_eq!(outcome.previous_state, StateType::AwaitingInfo);
+        assert_eq!(outcome.new_state, StateType::EMVPayment);
         
         // Deve receber evento de mudança de estado
         let event = timeout(Duration::from_secs(1), rx.recv()).await;
@@ -229,7 +238,7 @@ mod state_manager_tests {
         };
         
         let result = manager.execute(
-            EmvPaymentAction::CompletePayment { result: emv_result }
+            EmvPaymentAction::CompletePayment { result: Some(emv_result) }
         ).await;
         
         assert!(result.is_ok());
@@ -251,7 +260,7 @@ mod state_manager_tests {
         let (manager, mut rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
         
         let result = manager.execute(
-            EmvPaymentAction::CancelPayment
+            EmvPaymentAction::CancelPayment { reason: None }
         ).await;
         
         assert!(result.is_ok());
@@ -314,7 +323,7 @@ mod state_manager_tests {
         };
         
         manager.execute(
-            EmvPaymentAction::CompletePayment { result: emv_result }
+            EmvPaymentAction::CompletePayment { result: Some(emv_result) }
         ).await.unwrap();
         
         assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
@@ -483,11 +492,1672 @@ mod state_manager_tests {
         
         // Transição 2: EMVPayment -> AwaitingInfo (cancelamento)
         manager.execute(
-            EmvPaymentAction::CancelPayment
+            EmvPaymentAction::CancelPayment { reason: None }
         ).await.unwrap();
         
         let event2 = rx.recv().await.unwrap();
         assert_eq!(event2.from_state, StateType::EMVPayment);
         assert_eq!(event2.to_state, StateType::AwaitingInfo);
     }
+
+    // ==================== TESTES DE APROVAÇÃO PARCIAL ====================
+
+    /// Leva o manager até CollectRemainder com aprovação parcial de 60 de 100
+    async fn create_collect_remainder_manager() -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>) {
+        let (manager, rx) = create_emv_payment_manager(100.0, PaymentType::Credit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePartial {
+            result: EmvResult {
+                transaction_id: "TXN-PARTIAL".to_string(),
+                authorization_code: "AUTH-PARTIAL".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+            approved_amount: 60.0,
+        }).await.unwrap();
+        
+        (manager, rx)
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_transitions_to_collect_remainder() {
+        let (manager, mut rx) = create_collect_remainder_manager().await;
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::CollectRemainder);
+        
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.from_state, StateType::EMVPayment);
+        assert_eq!(event.to_state, StateType::CollectRemainder);
+        
+        let shortfall = manager.inspect::<CollectRemainder, _, _>(|state| state.shortfall).await.unwrap();
+        assert_eq!(shortfall, 40.0);
+    }
+
+    #[tokio::test]
+    async fn test_cash_covers_remainder_transitions_to_success() {
+        let (manager, _rx) = create_collect_remainder_manager().await;
+        
+        // Dinheiro acima do restante é rejeitado
+        let result = manager.execute(CollectRemainderAction::CollectCash { amount: 50.0 }).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("excede o restante"));
+        
+        // Pagamento parcial em dinheiro permanece no estado
+        manager.execute(CollectRemainderAction::CollectCash { amount: 15.0 }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::CollectRemainder);
+        
+        // Cobrir o restante conclui o pagamento
+        manager.execute(CollectRemainderAction::CollectCash { amount: 25.0 }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        
+        let (amount, split_tender) = manager.inspect::<PaymentSuccess, _, _>(|state| {
+            (state.payment_info.amount, state.split_tender.clone())
+        }).await.unwrap();
+        assert_eq!(amount.value(), 100.0);
+        assert_eq!(split_tender.cash.value(), 40.0);
+        assert_eq!(split_tender.partial_authorizations.len(), 1);
+        assert_eq!(split_tender.covered_cents(), 10000);
+    }
+
+    #[tokio::test]
+    async fn test_collect_cash_rejects_non_finite_values() {
+        let (manager, _rx) = create_collect_remainder_manager().await;
+        
+        for amount in [f64::NAN, f64::INFINITY, 0.001] {
+            let result = manager.execute(CollectRemainderAction::CollectCash { amount }).await;
+            assert!(result.is_err(), "{} deveria ser rejeitado", amount);
+        }
+        
+        let state = manager.inspect::<CollectRemainder, _, _>(|state| {
+            (state.shortfall, state.split_tender.cash)
+        }).await.unwrap();
+        assert_eq!(state, (40.0, Amount::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_second_card_returns_to_emv_for_remainder() {
+        let (manager, _rx) = create_collect_remainder_manager().await;
+        
+        manager.execute(CollectRemainderAction::SecondCard).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        let description = manager.get_description::<EMVPayment, _>(
+            |state| state.description()
+        ).await.unwrap();
+        assert!(description.contains("40.00"));
+        
+        // A venda original é mantida; só o valor devido diminui
+        let (amount, due) = manager.inspect::<EMVPayment, _, _>(|state| {
+            (state.payment_info.amount, state.amount_due())
+        }).await.unwrap();
+        assert_eq!(amount.value(), 100.0);
+        assert_eq!(due.value(), 40.0);
+    }
+
+    #[tokio::test]
+    async fn test_second_card_success_keeps_partial_authorization() {
+        let (manager, _rx) = create_collect_remainder_manager().await;
+        
+        manager.execute(CollectRemainderAction::SecondCard).await.unwrap();
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-SECOND".to_string(),
+                authorization_code: "AUTH-SECOND".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        
+        let (result, amount, partials) = manager.inspect::<PaymentSuccess, _, _>(|state| {
+            (state.result.clone(), state.payment_info.amount, state.split_tender.partial_authorizations.clone())
+        }).await.unwrap();
+        assert_eq!(result.transaction_id, "TXN-SECOND");
+        assert_eq!(amount.value(), 100.0);
+        assert_eq!(partials.len(), 1);
+        assert_eq!(partials[0].result.transaction_id, "TXN-PARTIAL");
+        assert_eq!(partials[0].result.authorization_code, "AUTH-PARTIAL");
+        assert_eq!(partials[0].approved_amount, 60.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_rejects_full_amount() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let result = manager.execute(EmvPaymentAction::CompletePartial {
+            result: EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+            approved_amount: 100.0,
+        }).await;
+        
+        assert!(result.is_err());
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_validates_cents_against_total() {
+        setup();
+        let payment_info = PaymentInfo {
+            amount: Amount::new(100.0).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: Amount::new(10.0).unwrap(),
+        };
+        let (manager, _rx) = StateManager::new(Box::new(EMVPayment::new(payment_info)), StateType::EMVPayment);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        let partial = |approved_amount: f64| EmvPaymentAction::CompletePartial {
+            result: EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+            approved_amount,
+        };
+        
+        // NaN, valores abaixo de um centavo e o total com gorjeta são rejeitados
+        for approved_amount in [f64::NAN, 0.004, 110.0] {
+            assert!(manager.execute(partial(approved_amount)).await.is_err());
+        }
+        
+        // Acima do valor sem gorjeta, mas abaixo do total, é parcial
+        manager.execute(partial(105.0)).await.unwrap();
+        let shortfall = manager.inspect::<CollectRemainder, _, _>(|state| state.shortfall).await.unwrap();
+        assert_eq!(shortfall, 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_payment_rejects_blank_result() {
+        let (manager, _rx) = create_emv_payment_manager(50.0, PaymentType::Debit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        let result = manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "  ".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await;
+        
+        assert!(result.unwrap_err().to_string().contains("Código de autorização"));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    // ==================== TESTES DE ATUALIZAÇÃO DO RESULTADO ====================
+
+    #[tokio::test]
+    async fn test_update_result_then_complete_uses_stored_result() {
+        let (manager, _rx) = create_emv_payment_manager(80.0, PaymentType::Credit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        // Re-tentativa de autorização gera novo código
+        manager.execute(EmvPaymentAction::UpdateResult {
+            result: EmvResult {
+                transaction_id: "TXN-RETRY".to_string(),
+                authorization_code: "AUTH-RETRY".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        // Completa sem repassar o resultado
+        manager.execute(EmvPaymentAction::CompletePayment { result: None }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        
+        let description = manager.get_description::<PaymentSuccess, _>(
+            |state| state.description()
+        ).await.unwrap();
+        assert!(description.contains("TXN-RETRY"));
+        assert!(description.contains("AUTH-RETRY"));
+    }
+
+    #[tokio::test]
+    async fn test_update_result_before_processing_is_rejected() {
+        let (manager, _rx) = create_emv_payment_manager(80.0, PaymentType::Credit);
+        
+        let result = manager.execute(EmvPaymentAction::UpdateResult {
+            result: EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+        }).await;
+        
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("não foi iniciado"));
+    }
+
+    #[tokio::test]
+    async fn test_update_result_rejects_empty_fields() {
+        let (manager, _rx) = create_emv_payment_manager(80.0, PaymentType::Credit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let result = manager.execute(EmvPaymentAction::UpdateResult {
+            result: EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "  ".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+        }).await;
+        
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_without_any_result_is_rejected() {
+        let (manager, _rx) = create_emv_payment_manager(80.0, PaymentType::Credit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let result = manager.execute(EmvPaymentAction::CompletePayment { result: None }).await;
+        
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Nenhum resultado"));
+    }
+
+    // ==================== TESTES DE ESTADO TERMINAL ====================
+
+    #[tokio::test]
+    async fn test_is_terminal_follows_payment_flow() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        assert!(!manager.is_terminal().await);
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 20.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        assert!(!manager.is_terminal().await);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert!(manager.is_terminal().await);
+    }
+
+    #[test]
+    fn test_is_terminal_per_state() {
+        let payment_info = PaymentInfo {
+            amount: Amount::new(10.0).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: Amount::ZERO,
+        };
+        let result = EmvResult {
+            transaction_id: "TXN".to_string(),
+            authorization_code: "AUTH".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        
+        assert!(!AwaitingInfo::initial().is_terminal());
+        assert!(!EMVPayment::new(payment_info.clone()).is_terminal());
+        assert!(!CollectRemainder {
+            payment_info: payment_info.clone(),
+            partial_result: result.clone(),
+            approved_amount: 5.0,
+            shortfall: 5.0,
+            split_tender: SplitTender::default(),
+        }.is_terminal());
+        assert!(PaymentSuccess {
+            payment_info: payment_info.clone(),
+            result,
+            approval_mode: ApprovalMode::Online,
+            tip_adjustments: Vec::new(),
+            split_tender: SplitTender::default(),
+            clock: default_clock(),
+        }.is_terminal());
+        assert!(PaymentFailed {
+            payment_info,
+            reason: FailureReason::Timeout,
+        }.is_terminal());
+    }
+
+    // ==================== TESTES DE VERIFICAÇÃO DE CONTA ====================
+
+    #[tokio::test]
+    async fn test_verification_zero_amount_flow_is_allowed() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Verification }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 0.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        let verification_only = manager.inspect::<EMVPayment, _, _>(|state| state.verification_only).await.unwrap();
+        assert!(verification_only);
+    }
+
+    #[tokio::test]
+    async fn test_verification_without_amount_defaults_to_zero() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Verification }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        let amount = manager.inspect::<EMVPayment, _, _>(|state| state.payment_info.amount).await.unwrap();
+        assert!(amount.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_normal_zero_amount_is_rejected() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 0.0 }).await.unwrap();
+        let result = manager.execute(AwaitingInfoAction::ConfirmInfo).await;
+        assert!(result.unwrap_err().to_string().contains("maior que zero"));
+        
+        // Valor zero definido como verificação não vale após trocar o tipo
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Verification }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 0.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Credit }).await.unwrap();
+        let result = manager.execute(AwaitingInfoAction::ConfirmInfo).await;
+        assert!(result.unwrap_err().to_string().contains("maior que zero"));
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    #[tokio::test]
+    async fn test_verification_zero_amount_before_type_is_allowed() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 0.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Verification }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    #[tokio::test]
+    async fn test_verification_with_positive_amount_is_rejected() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 10.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Verification }).await.unwrap();
+        let result = manager.execute(AwaitingInfoAction::ConfirmInfo).await;
+        
+        assert!(result.unwrap_err().to_string().contains("valor zero"));
+    }
+
+    // ==================== TESTES DE MOTIVO DE CANCELAMENTO ====================
+
+    #[tokio::test]
+    async fn test_high_value_cancel_without_reason_is_rejected() {
+        let (manager, _rx) = create_emv_payment_manager(5000.0, PaymentType::Credit);
+        
+        let result = manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await;
+        assert!(result.unwrap_err().to_string().contains("motivo obrigatório"));
+        
+        let result = manager.execute(EmvPaymentAction::CancelPayment { reason: Some("  ".to_string()) }).await;
+        assert!(result.unwrap_err().to_string().contains("motivo obrigatório"));
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    #[tokio::test]
+    async fn test_high_value_cancel_with_reason_is_accepted() {
+        let (manager, _rx) = create_emv_payment_manager(5000.0, PaymentType::Credit);
+        
+        manager.execute(EmvPaymentAction::CancelPayment {
+            reason: Some("Cliente desistiu da compra".to_string()),
+        }).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    #[tokio::test]
+    async fn test_low_value_cancel_without_reason_is_accepted() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    // ==================== TESTES DE JANELA DE CANCELAMENTO ====================
+
+    /// Cria um manager em EMVPayment já processando, com relógio manual
+    async fn create_processing_manager_with_clock(
+        amount: f64,
+    ) -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>, Arc<ManualClock>) {
+        setup();
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let emv_state = EMVPayment::new(PaymentInfo {
+            amount: Amount::new(amount).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: Amount::ZERO,
+        }).with_clock(clock.clone());
+        
+        let (manager, rx) = StateManager::new(Box::new(emv_state), StateType::EMVPayment);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        (manager, rx, clock)
+    }
+
+    #[tokio::test]
+    async fn test_cancel_within_grace_window_returns_to_awaiting_info() {
+        let (manager, _rx, clock) = create_processing_manager_with_clock(100.0).await;
+        
+        clock.advance(chrono::Duration::seconds(1));
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_after_grace_window_requires_void() {
+        let (manager, _rx, clock) = create_processing_manager_with_clock(100.0).await;
+        
+        clock.advance(chrono::Duration::seconds(10));
+        let result = manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await;
+        assert!(result.unwrap_err().to_string().contains("use VoidPayment"));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        manager.execute(EmvPaymentAction::VoidPayment {
+            reason: Some("Cliente desistiu".to_string()),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::Voided);
+        assert!(manager.is_terminal().await);
+        
+        let description = manager.get_description::<Voided, _>(|state| state.description()).await.unwrap();
+        assert!(description.contains("Cliente desistiu"));
+    }
+
+    #[tokio::test]
+    async fn test_void_before_processing_is_rejected() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        
+        let result = manager.execute(EmvPaymentAction::VoidPayment { reason: None }).await;
+        assert!(result.unwrap_err().to_string().contains("use CancelPayment"));
+    }
+
+    #[tokio::test]
+    async fn test_high_value_void_requires_reason() {
+        let (manager, _rx, clock) = create_processing_manager_with_clock(5000.0).await;
+        
+        clock.advance(chrono::Duration::seconds(10));
+        let result = manager.execute(EmvPaymentAction::VoidPayment { reason: None }).await;
+        assert!(result.unwrap_err().to_string().contains("motivo obrigatório"));
+    }
+
+
+    #[test]
+    fn test_payment_info_rejects_invalid_amount_on_deserialize() {
+        let valid: PaymentInfo = serde_json::from_str(r#"{"amount":12.5,"payment_type":"Credit"}"#).unwrap();
+        assert_eq!(valid.amount.value(), 12.5);
+        
+        let negative = serde_json::from_str::<PaymentInfo>(r#"{"amount":-1.0,"payment_type":"Credit"}"#);
+        assert!(negative.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_infinite_amount_is_rejected() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        let result = manager.execute(AwaitingInfoAction::SetAmount { amount: f64::INFINITY }).await;
+        assert!(result.is_err());
+        
+        let amount = manager.inspect::<AwaitingInfo, _, _>(|state| state.amount).await.unwrap();
+        assert!(amount.is_none());
+    }
+
+
+    fn offline_result() -> EmvResult {
+        EmvResult {
+            transaction_id: "PIX-1".to_string(),
+            authorization_code: "OFF".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_require_processing_flow_rejects_direct_complete() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 30.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Credit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        let result = manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(offline_result()),
+        }).await;
+        assert!(result.unwrap_err().to_string().contains("não foi iniciado"));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    #[tokio::test]
+    async fn test_relaxed_flow_completes_without_processing() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 30.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetRequireProcessing { require_processing: false }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        let require = manager.inspect::<EMVPayment, _, _>(|state| state.require_processing).await.unwrap();
+        assert!(!require);
+        
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(offline_result()),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+
+    #[tokio::test]
+    async fn test_undo_confirm_info_restores_awaiting_info() {
+        let (manager, mut rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 45.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Credit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        rx.recv().await.unwrap();
+        
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        
+        // O estado anterior volta com os dados já informados
+        let amount = manager.inspect::<AwaitingInfo, _, _>(|state| state.amount).await.unwrap();
+        assert_eq!(amount.map(|a| a.value()), Some(45.0));
+        
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.from_state, StateType::EMVPayment);
+        assert_eq!(event.to_state, StateType::AwaitingInfo);
+        
+        // Nenhuma transição anterior ao estado inicial
+        assert!(manager.undo().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_refused_after_processing_and_into_success() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        
+        let result = manager.undo().await;
+        assert!(result.unwrap_err().to_string().contains("não pode ser desfeita"));
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+
+    #[tokio::test]
+    async fn test_decline_transitions_to_declined_with_reason() {
+        let (manager, _rx) = create_emv_payment_manager(70.0, PaymentType::Credit);
+        
+        // Sem processamento não há autorização para recusar
+        let result = manager.execute(EmvPaymentAction::DeclinePayment { reason: "05".to_string() }).await;
+        assert!(result.is_err());
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::DeclinePayment {
+            reason: "51 - Saldo insuficiente".to_string(),
+        }).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::Declined);
+        assert!(manager.is_terminal().await);
+        let description = manager.get_description::<Declined, _>(|state| state.description()).await.unwrap();
+        assert!(description.contains("Saldo insuficiente"));
+    }
+
+    #[tokio::test]
+    async fn test_declined_retry_returns_to_prefilled_awaiting_info() {
+        let (manager, _rx) = create_emv_payment_manager(70.0, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::DeclinePayment { reason: "05".to_string() }).await.unwrap();
+        
+        manager.execute(DeclinedAction::Retry).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        
+        let (amount, payment_type) = manager
+            .inspect::<AwaitingInfo, _, _>(|state| (state.amount, state.payment_type.clone()))
+            .await
+            .unwrap();
+        assert_eq!(amount.map(|a| a.value()), Some(70.0));
+        assert_eq!(payment_type, Some(PaymentType::Credit));
+        
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+
+    /// Cria um manager em PaymentSuccess para o valor informado
+    async fn create_success_manager(amount: f64) -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>) {
+        let (manager, rx) = create_emv_payment_manager(amount, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-REF".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        (manager, rx)
+    }
+
+    #[tokio::test]
+    async fn test_partial_refund_transitions_to_refund() {
+        let (manager, _rx) = create_success_manager(100.0).await;
+        
+        manager.execute(PaymentSuccessAction::RefundPayment { amount: 40.0 }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::Refund);
+        assert!(manager.is_terminal().await);
+        
+        let (amount, original_id) = manager
+            .inspect::<Refund, _, _>(|state| (state.amount.value(), state.original_result.transaction_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(amount, 40.0);
+        assert_eq!(original_id, "TXN-REF");
+        
+        manager.execute(RefundAction::Reset).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    #[tokio::test]
+    async fn test_refund_amount_is_validated() {
+        let (manager, _rx) = create_success_manager(100.0).await;
+        
+        assert!(manager.execute(PaymentSuccessAction::RefundPayment { amount: 0.0 }).await.is_err());
+        assert!(manager.execute(PaymentSuccessAction::RefundPayment { amount: -5.0 }).await.is_err());
+        let result = manager.execute(PaymentSuccessAction::RefundPayment { amount: 100.01 }).await;
+        assert!(result.unwrap_err().to_string().contains("exceder"));
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        
+        // Reembolso total é permitido
+        manager.execute(PaymentSuccessAction::RefundPayment { amount: 100.0 }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::Refund);
+    }
+
+
+    #[tokio::test]
+    async fn test_cached_dispatch_updates_after_transition() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        assert_eq!(manager.cached_entry_type().await, Some(StateType::AwaitingInfo));
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 10.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        assert_eq!(manager.cached_entry_type().await, Some(StateType::EMVPayment));
+        
+        // O dispatch em cache é o do novo estado: ações antigas são rejeitadas
+        assert!(manager.execute(AwaitingInfoAction::ConfirmInfo).await.is_err());
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        assert!(manager.undo().await.is_err());
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        assert_eq!(manager.cached_entry_type().await, Some(StateType::AwaitingInfo));
+    }
+
+    #[tokio::test]
+    async fn test_cached_dispatch_hot_path_behaves_unchanged() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        for i in 1..=10_000 {
+            manager.execute(AwaitingInfoAction::SetAmount { amount: i as f64 }).await.unwrap();
+        }
+        
+        let amount = manager.inspect::<AwaitingInfo, _, _>(|state| state.amount).await.unwrap();
+        assert_eq!(amount.map(|a| a.value()), Some(10_000.0));
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    #[tokio::test]
+    async fn test_current_description_follows_transitions() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        assert_eq!(manager.current_description().await.unwrap(), "Aguardando informações do pagamento");
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 12.5 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        assert!(manager.current_description().await.unwrap().contains("Aguardando confirmação: R$ 12.50"));
+        
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        assert_eq!(
+            manager.current_description().await.unwrap(),
+            "Pronto para processar pagamento de R$ 12.50"
+        );
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        assert_eq!(
+            manager.current_description().await.unwrap(),
+            "Processando pagamento de R$ 12.50..."
+        );
+        
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-D".to_string(),
+                authorization_code: "AUTH-D".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        let description = manager.current_description().await.unwrap();
+        assert!(description.contains("TXN-D"));
+        assert_eq!(
+            description,
+            manager.get_description::<PaymentSuccess, _>(|state| state.description()).await.unwrap()
+        );
+    }
+
+    // ==================== TESTES DE CONFIRMAÇÃO DO PORTADOR ====================
+
+    #[tokio::test]
+    async fn test_complete_above_threshold_requires_cardholder_confirmation() {
+        let (manager, _rx) = create_emv_payment_manager(8000.0, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        let result = manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN1".to_string(),
+                authorization_code: "AUTH1".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await;
+        assert!(result.unwrap_err().to_string().contains("confirmação do portador"));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        manager.execute(EmvPaymentAction::ConfirmCardholder).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN1".to_string(),
+                authorization_code: "AUTH1".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_requires_confirmation_and_valid_result() {
+        let (manager, _rx) = create_emv_payment_manager(8000.0, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let partial = |authorization_code: &str| EmvPaymentAction::CompletePartial {
+            result: EmvResult {
+                transaction_id: "TXN-PART".to_string(),
+                authorization_code: authorization_code.to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            },
+            approved_amount: 6000.0,
+        };
+        
+        let result = manager.execute(partial("AUTH-PART")).await;
+        assert!(result.unwrap_err().to_string().contains("confirmação do portador"));
+        
+        manager.execute(EmvPaymentAction::ConfirmCardholder).await.unwrap();
+        let result = manager.execute(partial(" ")).await;
+        assert!(result.unwrap_err().to_string().contains("Código de autorização"));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        manager.execute(partial("AUTH-PART")).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::CollectRemainder);
+    }
+
+    #[tokio::test]
+    async fn test_complete_below_threshold_skips_cardholder_confirmation() {
+        let (manager, _rx) = create_emv_payment_manager(200.0, PaymentType::Debit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN2".to_string(),
+                authorization_code: "AUTH2".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+    // ==================== TESTES DE PERSISTÊNCIA ====================
+
+    /// Um exemplar de cada estado registrado
+    fn one_of_each_state() -> Vec<(StateType, Box<dyn std::any::Any + Send + Sync>)> {
+        setup();
+        let info = PaymentInfo {
+            amount: Amount::new(120.0).unwrap(),
+            payment_type: PaymentType::Credit,
+            tip: Amount::new(12.0).unwrap(),
+        };
+        let result = EmvResult {
+            transaction_id: "TXN77".to_string(),
+            authorization_code: "AUTH77".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut emv = EMVPayment::new(info.clone());
+        emv.processing = true;
+        emv.processing_started_at = Some(chrono::Utc::now());
+        emv.progress = 30;
+        
+        vec![
+            (StateType::AwaitingInfo, Box::new(AwaitingInfo::initial())),
+            (StateType::EMVPayment, Box::new(emv)),
+            (StateType::PaymentSuccess, Box::new(PaymentSuccess {
+                payment_info: info.clone(),
+                result: result.clone(),
+                approval_mode: ApprovalMode::OfflineStandIn,
+                tip_adjustments: Vec::new(),
+                split_tender: SplitTender::default(),
+                clock: default_clock(),
+            })),
+            (StateType::PaymentFailed, Box::new(PaymentFailed {
+                payment_info: info.clone(),
+                reason: FailureReason::Timeout,
+            })),
+            (StateType::CollectRemainder, Box::new(CollectRemainder {
+                payment_info: info.clone(),
+                partial_result: result.clone(),
+                approved_amount: 100.0,
+                shortfall: 20.0,
+                split_tender: SplitTender::default(),
+            })),
+            (StateType::Voided, Box::new(Voided {
+                payment_info: info.clone(),
+                reason: Some("cliente desistiu".to_string()),
+                voided_at: chrono::Utc::now().to_rfc3339(),
+            })),
+            (StateType::Declined, Box::new(Declined {
+                payment_info: info.clone(),
+                reason: DeclinedReason("saldo insuficiente".to_string()),
+            })),
+            (StateType::PixPayment, Box::new(PixPayment::new(info.clone(), &pix_merchant().unwrap()))),
+            (StateType::Refund, Box::new(Refund {
+                payment_info: info,
+                original_result: result,
+                amount: Amount::new(50.0).unwrap(),
+                refunded_at: chrono::Utc::now().to_rfc3339(),
+            })),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_round_trips_every_state() {
+        setup();
+        for (state_type, state) in one_of_each_state() {
+            let (manager, _rx) = StateManager::new(state, state_type);
+            let json = manager.snapshot().await.unwrap();
+            
+            let (restored, _rx) = StateManager::restore(&json).unwrap();
+            assert_eq!(restored.get_current_state_type().await, state_type);
+            assert_eq!(restored.snapshot().await.unwrap(), json, "{:?}", state_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restored_state_keeps_working() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let json = manager.snapshot().await.unwrap();
+        
+        let (restored, _rx) = StateManager::restore(&json).unwrap();
+        // O processamento iniciado foi preservado
+        let result = restored.execute(EmvPaymentAction::ProcessPayment).await;
+        assert!(result.is_err());
+        restored.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN78".to_string(),
+                authorization_code: "AUTH78".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(restored.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+    #[test]
+    fn test_restore_rejects_invalid_snapshot() {
+        setup();
+        assert!(StateManager::restore("não é json").is_err());
+        assert!(StateManager::restore(r#"{"state_type":"Voided","state":{}}"#).is_err());
+    }
+
+    // ==================== TESTES DE COBRANÇA COMPLEMENTAR ====================
+
+    #[tokio::test]
+    async fn test_supplemental_charge_starts_new_emv_payment_with_reference() {
+        let (manager, _rx) = create_success_manager(100.0).await;
+        
+        manager.execute(PaymentSuccessAction::SupplementalCharge { amount: 15.5 }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        
+        let (amount, original) = manager.inspect(|state: &EMVPayment| {
+            (state.payment_info.amount, state.original_transaction_id.clone())
+        }).await.unwrap();
+        assert_eq!(amount, Amount::new(15.5).unwrap());
+        assert_eq!(original.as_deref(), Some("TXN-REF"));
+    }
+
+    #[tokio::test]
+    async fn test_supplemental_charge_rejects_non_positive_amount() {
+        let (manager, _rx) = create_success_manager(100.0).await;
+        
+        for amount in [0.0, -5.0, f64::NAN] {
+            let result = manager.execute(PaymentSuccessAction::SupplementalCharge { amount }).await;
+            assert!(result.is_err(), "{}", amount);
+        }
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+    #[tokio::test]
+    async fn test_supplemental_charge_rejects_verification_and_pix_originals() {
+        setup();
+        for payment_type in [PaymentType::Verification, PaymentType::Pix] {
+            let state = PaymentSuccess {
+                payment_info: PaymentInfo {
+                    amount: Amount::new(50.0).unwrap(),
+                    payment_type: payment_type.clone(),
+                    tip: Amount::ZERO,
+                },
+                result: EmvResult {
+                    transaction_id: "TXN-SUP".to_string(),
+                    authorization_code: "AUTH".to_string(),
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                },
+                approval_mode: ApprovalMode::Online,
+                tip_adjustments: Vec::new(),
+                split_tender: SplitTender::default(),
+                clock: default_clock(),
+            };
+            let (manager, _rx) = StateManager::new(Box::new(state), StateType::PaymentSuccess);
+            
+            let result = manager.execute(PaymentSuccessAction::SupplementalCharge { amount: 10.0 }).await;
+            assert!(result.unwrap_err().to_string().contains("não disponível"), "{:?}", payment_type);
+            assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        }
+    }
+
+    // ==================== TESTES DE AÇÕES DISPONÍVEIS ====================
+
+    #[tokio::test]
+    async fn test_available_actions_follow_transitions() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        assert_eq!(
+            manager.available_actions().await.unwrap(),
+            ["SetAmount", "SetPaymentType", "SetTip", "SetRequireProcessing", "ConfirmInfo"]
+        );
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 60.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Debit 
+        }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        let actions = manager.available_actions().await.unwrap();
+        assert!(actions.contains(&"ProcessPayment".to_string()));
+        assert!(actions.contains(&"CompletePayment".to_string()));
+        assert!(!actions.contains(&"SetAmount".to_string()));
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-ACT".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(
+            manager.available_actions().await.unwrap(),
+            ["Reset", "RefundPayment", "SupplementalCharge", "AdjustTip"]
+        );
+        
+        manager.execute(PaymentSuccessAction::Reset).await.unwrap();
+        assert_eq!(manager.available_actions().await.unwrap()[0], "SetAmount");
+    }
+
+    // ==================== TESTES DE ATOMICIDADE DO EXECUTE ====================
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_execute_emits_consistent_transitions() {
+        let (manager, mut rx) = create_awaiting_info_manager();
+        
+        // Cada tarefa tenta avançar para EMVPayment e voltar via cancelamento;
+        // a maioria das ações falha por disputar o estado com as demais
+        let handles: Vec<_> = (0..16).map(|_| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                for _ in 0..25 {
+                    let _ = manager.execute(AwaitingInfoAction::SetAmount { amount: 30.0 }).await;
+                    let _ = manager.execute(AwaitingInfoAction::SetPaymentType { 
+                        payment_type: PaymentType::Credit 
+                    }).await;
+                    let _ = manager.execute(AwaitingInfoAction::ConfirmInfo).await;
+                    let _ = manager.execute(EmvPaymentAction::CancelPayment {
+                        reason: Some("teste".to_string()),
+                    }).await;
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(!events.is_empty());
+        
+        // Só existem os pares AwaitingInfo <-> EMVPayment, sempre encadeados
+        let mut expected_from = StateType::AwaitingInfo;
+        for event in &events {
+            assert_ne!(event.from_state, event.to_state);
+            assert_eq!(event.from_state, expected_from, "evento {}", event.seq);
+            assert!(matches!(
+                (event.from_state, event.to_state),
+                (StateType::AwaitingInfo, StateType::EMVPayment)
+                    | (StateType::EMVPayment, StateType::AwaitingInfo)
+            ));
+            expected_from = event.to_state;
+        }
+        assert_eq!(manager.get_current_state_type().await, expected_from);
+    }
+
+    // ==================== TESTES DE LIMITES DE VALOR ====================
+
+    #[tokio::test]
+    async fn test_set_amount_rejects_values_outside_acquirer_limits() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        let result = manager.execute(AwaitingInfoAction::SetAmount { amount: 0.004 }).await;
+        assert!(result.unwrap_err().to_string().contains("abaixo do limite permitido (R$ 0.01)"));
+        
+        let result = manager.execute(AwaitingInfoAction::SetAmount { amount: 50_000.01 }).await;
+        assert!(result.unwrap_err().to_string().contains("Valor acima do limite permitido (R$ 50000.00)"));
+        
+        // Negativos mantêm a mensagem original
+        let result = manager.execute(AwaitingInfoAction::SetAmount { amount: -1.0 }).await;
+        assert!(result.unwrap_err().to_string().contains("maior que zero"));
+    }
+
+    #[tokio::test]
+    async fn test_set_amount_accepts_limit_boundaries() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 0.01 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 50_000.0 }).await.unwrap();
+    }
+
+    // ==================== TESTES DE MODO DE APROVAÇÃO ====================
+
+    async fn complete_in_mode(offline: bool) -> StateManager {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        let result = Some(EmvResult {
+            transaction_id: "TXN-MODE".to_string(),
+            authorization_code: "AUTH".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+        let action = if offline {
+            EmvPaymentAction::CompleteOfflineStandIn { result }
+        } else {
+            EmvPaymentAction::CompletePayment { result }
+        };
+        manager.execute(action).await.unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_complete_payment_records_online_approval() {
+        let manager = complete_in_mode(false).await;
+        
+        let mode = manager.inspect(|state: &PaymentSuccess| state.approval_mode).await.unwrap();
+        assert_eq!(mode, ApprovalMode::Online);
+        assert!(manager.current_description().await.unwrap().contains("Aprovação: online"));
+    }
+
+    #[tokio::test]
+    async fn test_offline_stand_in_approval_is_recorded() {
+        let manager = complete_in_mode(true).await;
+        
+        let mode = manager.inspect(|state: &PaymentSuccess| state.approval_mode).await.unwrap();
+        assert_eq!(mode, ApprovalMode::OfflineStandIn);
+        assert!(manager.current_description().await.unwrap().contains("offline (stand-in)"));
+        assert!(manager.snapshot().await.unwrap().contains("\"approval_mode\":\"OfflineStandIn\""));
+    }
+
+    // ==================== TESTES DE PIX ====================
+
+    async fn confirm_pix(amount: f64) -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>) {
+        let (manager, rx) = create_awaiting_info_manager();
+        manager.execute(AwaitingInfoAction::SetAmount { amount }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Pix 
+        }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        (manager, rx)
+    }
+
+    #[tokio::test]
+    async fn test_confirm_info_routes_pix_to_pix_payment() {
+        let (manager, _rx) = confirm_pix(75.0).await;
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::PixPayment);
+        let payload = manager.inspect(|state: &PixPayment| state.qr_payload.clone()).await.unwrap();
+        assert!(payload.contains("540575.00"));
+        assert!(payload.contains("0116loja@example.com"));
+        assert!(payload.contains("5912LOJA EXEMPLO"));
+        assert!(payload.contains("6009SAO PAULO"));
+        
+        // Ações EMV não se aplicam ao PIX
+        assert!(manager.execute(EmvPaymentAction::ProcessPayment).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_pix_received_completes_payment() {
+        let (manager, _rx) = confirm_pix(75.0).await;
+        let txid = manager.inspect(|state: &PixPayment| state.txid.clone()).await.unwrap();
+        
+        manager.execute(PixPaymentAction::ConfirmPixReceived {
+            end_to_end_id: "E2E-123".to_string(),
+        }).await.unwrap();
+        
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        let result = manager.inspect(|state: &PaymentSuccess| state.result.clone()).await.unwrap();
+        assert_eq!(result.transaction_id, txid);
+        assert_eq!(result.authorization_code, "E2E-123");
+    }
+
+    #[tokio::test]
+    async fn test_pix_requires_positive_amount() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        manager.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Pix 
+        }).await.unwrap();
+        
+        let result = manager.execute(AwaitingInfoAction::ConfirmInfo).await;
+        assert!(result.is_err());
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
+
+    // ==================== TESTES DE GORJETA ====================
+
+    #[tokio::test]
+    async fn test_set_tip_is_carried_into_payment_info() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 100.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetTip { tip: 10.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { 
+            payment_type: PaymentType::Credit 
+        }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        let (tip, total) = manager.inspect(|state: &EMVPayment| {
+            (state.payment_info.tip, state.payment_info.total())
+        }).await.unwrap();
+        assert_eq!(tip, Amount::new(10.0).unwrap());
+        assert_eq!(total, Amount::new(110.0).unwrap());
+        assert_eq!(
+            manager.current_description().await.unwrap(),
+            "Pronto para processar pagamento de R$ 100.00 + Gorjeta R$ 10.00"
+        );
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-TIP".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert!(manager.current_description().await.unwrap()
+            .contains("Valor: R$ 100.00 + Gorjeta R$ 10.00"));
+    }
+
+    #[tokio::test]
+    async fn test_set_tip_rejects_negative_values() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        let result = manager.execute(AwaitingInfoAction::SetTip { tip: -1.0 }).await;
+        assert!(result.unwrap_err().to_string().contains("Gorjeta não pode ser negativa"));
+        manager.execute(AwaitingInfoAction::SetTip { tip: 0.0 }).await.unwrap();
+    }
+
+    // ==================== TESTES DE FALLBACK PARA TARJA ====================
+
+    #[tokio::test]
+    async fn test_three_chip_read_failures_enable_swipe_fallback() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Credit);
+        
+        for attempt in 1..MAX_CHIP_READ_ATTEMPTS {
+            manager.execute(EmvPaymentAction::ChipReadFailed).await.unwrap();
+            let (attempts, fallback) = manager.inspect(|state: &EMVPayment| {
+                (state.read_attempts, state.fallback)
+            }).await.unwrap();
+            assert_eq!(attempts, attempt);
+            assert!(!fallback);
+        }
+        
+        manager.execute(EmvPaymentAction::ChipReadFailed).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        assert!(manager.inspect(|state: &EMVPayment| state.fallback).await.unwrap());
+        assert!(manager.current_description().await.unwrap().contains("tarja magnética"));
+        
+        // Nova falha após o fallback é rejeitada
+        assert!(manager.execute(EmvPaymentAction::ChipReadFailed).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_payment_after_swipe_fallback() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Debit);
+        for _ in 0..MAX_CHIP_READ_ATTEMPTS {
+            manager.execute(EmvPaymentAction::ChipReadFailed).await.unwrap();
+        }
+        
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-SWIPE".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+    }
+
+    // ==================== TESTES DA PILHA DE UNDO ====================
+
+    async fn confirm(manager: &StateManager, amount: f64) {
+        manager.execute(AwaitingInfoAction::SetAmount { amount }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_steps_back_through_awaiting_info() {
+        let (manager, mut rx) = create_awaiting_info_manager();
+        
+        // AwaitingInfo -> EMVPayment(30) -> AwaitingInfo -> EMVPayment(60)
+        confirm(&manager, 30.0).await;
+        manager.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+        confirm(&manager, 60.0).await;
+        for _ in 0..3 {
+            rx.recv().await.unwrap();
+        }
+        
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        let amount = manager.inspect(|state: &AwaitingInfo| state.amount).await.unwrap();
+        assert_eq!(amount.map(|a| a.value()), Some(60.0));
+        
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        let amount = manager.inspect(|state: &EMVPayment| state.payment_info.amount).await.unwrap();
+        assert_eq!(amount.value(), 30.0);
+        
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        assert!(manager.undo().await.unwrap_err().to_string().contains("Nenhuma transição"));
+        
+        // Cada undo emite a transição no sentido inverso
+        let reversed: Vec<_> = (0..3)
+            .map(|_| rx.try_recv().map(|e| (e.from_state, e.to_state)).unwrap())
+            .collect();
+        assert_eq!(reversed, [
+            (StateType::EMVPayment, StateType::AwaitingInfo),
+            (StateType::AwaitingInfo, StateType::EMVPayment),
+            (StateType::EMVPayment, StateType::AwaitingInfo),
+        ]);
+        
+        // Após voltar, o fluxo segue normalmente
+        confirm(&manager, 90.0).await;
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+    #[tokio::test]
+    async fn test_undo_stack_is_capped_by_budget() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        assert_eq!(manager.memory_budget().await.max_undo, DEFAULT_MAX_UNDO);
+        manager.set_memory_budget(MemoryBudget { max_undo: 2, ..MemoryBudget::default() }).await;
+        
+        for amount in [10.0, 20.0] {
+            confirm(&manager, amount).await;
+            manager.execute(EmvPaymentAction::CancelPayment { reason: Some("teste".to_string()) }).await.unwrap();
+        }
+        
+        // Quatro transições, apenas as duas últimas podem ser desfeitas
+        manager.undo().await.unwrap();
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        assert!(manager.undo().await.is_err());
+    }
+
+    // ==================== TESTES DO RESULTADO TIPADO ====================
+
+    #[tokio::test]
+    async fn test_outcome_for_no_op_action() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Credit);
+        
+        let outcome = manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        assert_eq!(outcome, TransitionOutcome {
+            transitioned: false,
+            new_state: StateType::EMVPayment,
+            previous_state: StateType::EMVPayment,
+        });
+        assert_eq!(outcome.to_string(), "Ação executada - permanece no mesmo estado");
+    }
+
+    #[tokio::test]
+    async fn test_outcome_for_transitions() {
+        let (manager, _rx) = create_emv_payment_manager(100.0, PaymentType::Credit);
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        
+        let outcome = manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-OUT".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        assert!(outcome.transitioned);
+        assert_eq!(outcome.previous_state, StateType::EMVPayment);
+        assert_eq!(outcome.new_state, StateType::PaymentSuccess);
+        assert_eq!(outcome.to_string(), "Transicionado para PaymentSuccess");
+        
+        let outcome = manager.execute(PaymentSuccessAction::Reset).await.unwrap();
+        assert!(outcome.transitioned);
+        assert_eq!(outcome.previous_state, StateType::PaymentSuccess);
+        assert_eq!(outcome.new_state, StateType::AwaitingInfo);
+    }
+
+    // ==================== TESTES DE HOOKS DE ENTRADA/SAÍDA ====================
+
+    #[tokio::test]
+    async fn test_enter_exit_hooks_fire_in_order() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        
+        let record = |label: &'static str| -> StateHook {
+            let calls = Arc::clone(&calls);
+            Arc::new(move || calls.lock().unwrap().push(label))
+        };
+        manager.on_exit(StateType::AwaitingInfo, record("exit AwaitingInfo"));
+        manager.on_enter(StateType::EMVPayment, record("enter EMVPayment"));
+        manager.on_enter(StateType::PaymentSuccess, record("enter PaymentSuccess"));
+        
+        // Ações sem transição não disparam hooks
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 40.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        timeout(Duration::from_secs(1), async {
+            while calls.lock().unwrap().len() < 2 {
+                tokio::task::yield_now().await;
+            }
+        }).await.unwrap();
+        
+        assert_eq!(*calls.lock().unwrap(), ["exit AwaitingInfo", "enter EMVPayment"]);
+    }
+
+    #[tokio::test]
+    async fn test_hooks_keep_order_across_transitions() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        
+        let slow_calls = Arc::clone(&calls);
+        manager.on_enter(StateType::EMVPayment, Arc::new(move || {
+            // Hook lento: o da transição seguinte ainda deve rodar depois
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            slow_calls.lock().unwrap().push("enter EMVPayment");
+        }));
+        let fast_calls = Arc::clone(&calls);
+        manager.on_enter(StateType::AwaitingInfo, Arc::new(move || {
+            fast_calls.lock().unwrap().push("enter AwaitingInfo");
+        }));
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 40.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        
+        timeout(Duration::from_secs(2), async {
+            while calls.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.unwrap();
+        
+        assert_eq!(*calls.lock().unwrap(), ["enter EMVPayment", "enter AwaitingInfo"]);
+    }
+
+    #[tokio::test]
+    async fn test_slow_hook_does_not_block_transition() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        manager.on_enter(StateType::EMVPayment, Arc::new(|| {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+        }));
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 40.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        let started = std::time::Instant::now();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        assert!(started.elapsed() < std::time::Duration::from_millis(300));
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+    }
+
+
+    /// Cria um manager em PaymentSuccess com gorjeta e relógio manual parado na aprovação
+    fn create_tipped_success_manager(
+        amount: f64,
+        tip: f64,
+    ) -> (StateManager, tokio::sync::mpsc::UnboundedReceiver<StateChangeEvent>, Arc<ManualClock>) {
+        setup();
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let state = PaymentSuccess {
+            payment_info: PaymentInfo {
+                amount: Amount::new(amount).unwrap(),
+                payment_type: PaymentType::Credit,
+                tip: Amount::new(tip).unwrap(),
+            },
+            result: EmvResult {
+                transaction_id: "TXN-TIP".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: clock.now().to_rfc3339(),
+            },
+            approval_mode: ApprovalMode::Online,
+            tip_adjustments: Vec::new(),
+            split_tender: SplitTender::default(),
+            clock: default_clock(),
+        }.with_clock(clock.clone());
+        let (manager, rx) = StateManager::new(Box::new(state), StateType::PaymentSuccess);
+        (manager, rx, clock)
+    }
+
+    #[tokio::test]
+    async fn test_refund_caps_at_total_with_tip_and_uses_clock() {
+        let (manager, _rx, clock) = create_tipped_success_manager(100.0, 10.0);
+        
+        let result = manager.execute(PaymentSuccessAction::RefundPayment { amount: 110.01 }).await;
+        assert!(result.unwrap_err().to_string().contains("R$ 110.00"));
+        
+        manager.execute(PaymentSuccessAction::RefundPayment { amount: 110.0 }).await.unwrap();
+        let refunded_at = manager.inspect(|state: &Refund| state.refunded_at.clone()).await.unwrap();
+        assert_eq!(refunded_at, clock.now().to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn test_adjust_tip_downward_recomputes_total() {
+        let (manager, _rx, _clock) = create_tipped_success_manager(100.0, 20.0);
+        
+        let outcome = manager.execute(PaymentSuccessAction::AdjustTip { new_tip: 5.0 }).await.unwrap();
+        assert!(!outcome.transitioned);
+        assert_eq!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        
+        let (total, adjustments) = manager
+            .inspect(|state: &PaymentSuccess| (state.payment_info.total(), state.tip_adjustments.clone()))
+            .await
+            .unwrap();
+        assert_eq!(total.value(), 105.0);
+        assert_eq!(adjustments.len(), 1);
+        let TipAdjustment { previous_tip, new_tip, .. } = &adjustments[0];
+        assert_eq!((previous_tip.value(), new_tip.value()), (20.0, 5.0));
+    }
+
+    #[tokio::test]
+    async fn test_adjust_tip_rejects_negative_total() {
+        let (manager, _rx, _clock) = create_tipped_success_manager(100.0, 20.0);
+        
+        let result = manager.execute(PaymentSuccessAction::AdjustTip { new_tip: -150.0 }).await;
+        assert!(result.unwrap_err().to_string().contains("negativo"));
+        
+        let (tip, adjustments) = manager
+            .inspect(|state: &PaymentSuccess| (state.payment_info.tip.value(), state.tip_adjustments.len()))
+            .await
+            .unwrap();
+        assert_eq!((tip, adjustments), (20.0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_adjust_tip_enforces_uplift_cap() {
+        // Autorizado 120.00: teto de 20% = 144.00
+        let (manager, _rx, _clock) = create_tipped_success_manager(100.0, 20.0);
+        let result = manager.execute(PaymentSuccessAction::AdjustTip { new_tip: 44.01 }).await;
+        assert!(result.unwrap_err().to_string().contains("teto"));
+        manager.execute(PaymentSuccessAction::AdjustTip { new_tip: 44.0 }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_adjust_tip_window_boundary() {
+        let (manager, _rx, clock) = create_tipped_success_manager(100.0, 20.0);
+        
+        // Exatamente no limite do prazo ainda é aceito
+        clock.advance(chrono::Duration::hours(TIP_ADJUSTMENT_WINDOW_HOURS));
+        manager.execute(PaymentSuccessAction::AdjustTip { new_tip: 10.0 }).await.unwrap();
+        
+        clock.advance(chrono::Duration::seconds(1));
+        let result = manager.execute(PaymentSuccessAction::AdjustTip { new_tip: 5.0 }).await;
+        assert!(result.unwrap_err().to_string().contains("expirado"));
+    }
+
+
+    #[tokio::test]
+    async fn test_dropped_receiver_does_not_fail_committed_transition() {
+        let (manager, rx) = create_awaiting_info_manager();
+        let mut state_events = manager.subscribe_state_events();
+        drop(rx);
+        
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 30.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        let outcome = manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        
+        assert!(outcome.transitioned);
+        assert_eq!(manager.get_current_state_type().await, StateType::EMVPayment);
+        assert_eq!(manager.recent_events().await.len(), 1);
+        assert!(matches!(state_events.try_recv(), Ok(StateEvent::Changed(_))));
+        assert!(state_events.try_recv().is_err());
+    }
+
+
+    // ==================== TESTES DE EVENTOS OBSOLETOS ====================
+
+    /// Ida e volta AwaitingInfo -> EMVPayment -> AwaitingInfo (seqs 1 e 2)
+    async fn round_trip(manager: &StateManager) {
+        manager.execute(AwaitingInfoAction::SetAmount { amount: 30.0 }).await.unwrap();
+        manager.execute(AwaitingInfoAction::SetPaymentType { payment_type: PaymentType::Debit }).await.unwrap();
+        manager.execute(AwaitingInfoAction::ConfirmInfo).await.unwrap();
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mark_superseded_ignores_future_seqs() {
+        let (manager, mut rx) = create_awaiting_info_manager();
+        
+        // Seq 1 ainda não foi emitido: a marca não pode valer para o evento futuro
+        manager.mark_superseded(1).await;
+        round_trip(&manager).await;
+        
+        let event = manager.resolve_superseded(rx.recv().await.unwrap()).await;
+        assert_eq!(event.seq, 1);
+        assert!(!event.superseded);
+    }
+
+    #[tokio::test]
+    async fn test_superseded_marks_are_pruned_after_delivery_and_trim() {
+        let (manager, mut rx) = create_awaiting_info_manager();
+        round_trip(&manager).await;
+        
+        // Entregar o seq 2 descarta a marca do seq 1, que não foi consumida
+        manager.mark_superseded(1).await;
+        manager.mark_superseded(2).await;
+        let _ = rx.recv().await.unwrap();
+        let event = manager.resolve_superseded(rx.recv().await.unwrap()).await;
+        assert!(event.superseded);
+        let stale = manager.recent_events().await[0].clone();
+        assert!(!manager.peek_superseded(stale).await.superseded);
+        
+        // Marcas de eventos fora do buffer de replay são descartadas
+        round_trip(&manager).await;
+        manager.mark_superseded(3).await;
+        manager.set_memory_budget(MemoryBudget { max_events: 1, ..MemoryBudget::default() }).await;
+        let mut trimmed = manager.recent_events().await[0].clone();
+        trimmed.seq = 3;
+        assert!(!manager.peek_superseded(trimmed).await.superseded);
+    }
+
+
+    #[tokio::test]
+    async fn test_undo_after_reset_never_reopens_completed_sale() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        confirm(&manager, 100.0).await;
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-UNDO".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        manager.execute(PaymentSuccessAction::Reset).await.unwrap();
+        
+        let _ = manager.undo().await;
+        assert_ne!(manager.get_current_state_type().await, StateType::PaymentSuccess);
+        assert!(manager.execute(PaymentSuccessAction::RefundPayment { amount: 100.0 }).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_never_restores_processing_emv_payment() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        confirm(&manager, 40.0).await;
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        // Cancelamento dentro da janela: a autorização pode já ter saído
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        
+        let _ = manager.undo().await;
+        let processing = manager.inspect(|state: &EMVPayment| state.processing).await;
+        assert!(!processing.unwrap_or(false));
+    }
+
+
+    #[tokio::test]
+    async fn test_undo_stack_is_cleared_by_completed_sale() {
+        let (manager, _rx) = create_awaiting_info_manager();
+        
+        // Venda anterior cancelada antes do processamento: ainda reversível
+        confirm(&manager, 10.0).await;
+        manager.execute(EmvPaymentAction::CancelPayment { reason: None }).await.unwrap();
+        confirm(&manager, 20.0).await;
+        manager.execute(EmvPaymentAction::ProcessPayment).await.unwrap();
+        manager.execute(EmvPaymentAction::CompletePayment {
+            result: Some(EmvResult {
+                transaction_id: "TXN-CLR".to_string(),
+                authorization_code: "AUTH".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+        }).await.unwrap();
+        manager.execute(PaymentSuccessAction::Reset).await.unwrap();
+        
+        let result = manager.undo().await;
+        assert!(result.unwrap_err().to_string().contains("Nenhuma transição"));
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+        
+        // Após a nova venda o undo volta a funcionar normalmente
+        confirm(&manager, 30.0).await;
+        manager.undo().await.unwrap();
+        assert_eq!(manager.get_current_state_type().await, StateType::AwaitingInfo);
+    }
 }
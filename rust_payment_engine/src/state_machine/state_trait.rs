@@ -26,4 +26,17 @@ pub trait PaymentState<Action>: Send + Sync {
     
     /// Retorna uma descrição do estado
     fn description(&self) -> String;
+    
+    /// Indica se o estado encerra o fluxo (ex: exibir "nova venda")
+    fn is_terminal(&self) -> bool {
+        false
+    }
+    
+    /// Indica se a transição que levou a este estado pode ser desfeita (undo)
+    /// 
+    /// Padrão: irreversível. Estados só devem permitir undo enquanto nada
+    /// externo (ex: autorização enviada) depender deles.
+    fn is_reversible(&self) -> bool {
+        false
+    }
 }
@@ -1,5 +1,9 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, FieldSchema, StateSchema};
+use super::super::StateType;
+use crate::money::{from_cents, to_cents, Amount};
+use super::super::config::{amount_limits, pix_merchant};
 
 // ==================== TYPES DESTE ESTADO ====================
 
@@ -8,36 +12,84 @@ use serde::{Deserialize, Serialize};
 pub enum PaymentType {
     Debit,
     Credit,
+    /// Verificação de conta (autorização de valor zero, sem cobrança)
+    Verification,
+    /// PIX: pagamento via QR code, sem fluxo EMV
+    Pix,
 }
 
 /// Informações necessárias para iniciar um pagamento
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentInfo {
-    pub amount: f64,
+    pub amount: Amount,
     pub payment_type: PaymentType,
+    /// Gorjeta, cobrada junto com o valor
+    #[serde(default)]
+    pub tip: Amount,
+}
+
+impl PaymentInfo {
+    /// Valor total cobrado (valor + gorjeta)
+    pub fn total(&self) -> Amount {
+        Amount::new(from_cents(self.amount.cents() + self.tip.cents())).unwrap_or(self.amount)
+    }
+    
+    /// Valor para exibição, ex: "R$ 100.00 + Gorjeta R$ 10.00"
+    pub fn amount_label(&self) -> String {
+        if self.tip.is_zero() {
+            format!("R$ {:.2}", self.amount)
+        } else {
+            format!("R$ {:.2} + Gorjeta R$ {:.2}", self.amount, self.tip)
+        }
+    }
 }
 
 /// Ações válidas no estado AwaitingInfo
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum AwaitingInfoAction {
     SetAmount { amount: f64 },
     SetPaymentType { payment_type: PaymentType },
+    /// Gorjeta opcional (>= 0)
+    SetTip { tip: f64 },
+    /// Define se o fluxo exige ProcessPayment antes de CompletePayment
+    /// (fluxos offline/Pix podem concluir diretamente)
+    SetRequireProcessing { require_processing: bool },
     ConfirmInfo,
 }
 
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::AwaitingInfo,
+    actions: &[
+        ActionSchema { name: "SetAmount", params: &[FieldSchema { name: "amount", ty: "f64" }] },
+        ActionSchema { name: "SetPaymentType", params: &[FieldSchema { name: "payment_type", ty: "PaymentType" }] },
+        ActionSchema { name: "SetTip", params: &[FieldSchema { name: "tip", ty: "f64" }] },
+        ActionSchema { name: "SetRequireProcessing", params: &[FieldSchema { name: "require_processing", ty: "bool" }] },
+        ActionSchema { name: "ConfirmInfo", params: &[] },
+    ],
+    transitions: &[StateType::EMVPayment, StateType::PixPayment],
+};
+
 // ==================== ESTADO ====================
 
 /// Estado inicial - aguardando informações do pagamento
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwaitingInfo {
-    pub amount: Option<f64>,
+    pub amount: Option<Amount>,
     pub payment_type: Option<PaymentType>,
+    /// Gorjeta informada (ausente equivale a zero)
+    #[serde(default)]
+    pub tip: Option<Amount>,
+    /// Política repassada ao EMVPayment ao confirmar
+    pub require_processing: bool,
 }
 
 // ==================== IMPLEMENTAÇÃO DO TRAIT ====================
 
 use super::super::state_trait::PaymentState;
 use super::emv_payment::EMVPayment;
+use super::pix_payment::PixPayment;
 
 impl PaymentState<AwaitingInfoAction> for AwaitingInfo {
     /// Executa ação - CONSTRÓI próximo estado se houver transição
@@ -49,8 +101,18 @@ impl PaymentState<AwaitingInfoAction> for AwaitingInfo {
         
         match action {
             AwaitingInfoAction::SetAmount { amount } => {
-                if amount <= 0.0 {
-                    return Err(anyhow::anyhow!("Valor deve ser maior que zero"));
+                // Valida na fronteira: f64 vindo da FFI vira Amount
+                let amount = Amount::new(amount)
+                    .map_err(|_| anyhow::anyhow!("Valor deve ser maior que zero"))?;
+                // Valor zero só vale para verificação de conta, checado em
+                // ConfirmInfo para não depender da ordem das ações
+                // Limites do adquirente (comparados em centavos)
+                let (min, max) = amount_limits();
+                if !amount.is_zero() && amount.cents() < to_cents(min) {
+                    return Err(anyhow::anyhow!("Valor abaixo do limite permitido (R$ {:.2})", min));
+                }
+                if amount.cents() > to_cents(max) {
+                    return Err(anyhow::anyhow!("Valor acima do limite permitido (R$ {:.2})", max));
                 }
                 self.amount = Some(amount);
                 Ok(None)
@@ -61,19 +123,57 @@ impl PaymentState<AwaitingInfoAction> for AwaitingInfo {
                 Ok(None)
             }
             
+            AwaitingInfoAction::SetTip { tip } => {
+                let tip = Amount::new(tip)
+                    .map_err(|_| anyhow::anyhow!("Gorjeta não pode ser negativa"))?;
+                self.tip = Some(tip);
+                Ok(None)
+            }
+            
+            AwaitingInfoAction::SetRequireProcessing { require_processing } => {
+                self.require_processing = require_processing;
+                Ok(None)
+            }
+            
             AwaitingInfoAction::ConfirmInfo => {
-                let amount = self.amount.ok_or_else(|| anyhow::anyhow!("Valor não definido"))?;
                 let payment_type = self.payment_type.clone()
                     .ok_or_else(|| anyhow::anyhow!("Tipo de pagamento não definido"))?;
                 
-                // CONSTRÓI o próximo estado AQUI
-                let payment_info = PaymentInfo { amount, payment_type };
-                let next_state = EMVPayment {
-                    payment_info,
-                    processing: false,
-                    emv_result: None,
+                let amount = if payment_type == PaymentType::Verification {
+                    // Verificação não cobra: valor ausente equivale a zero
+                    let amount = self.amount.unwrap_or(Amount::ZERO);
+                    if !amount.is_zero() {
+                        return Err(anyhow::anyhow!("Verificação de conta deve ter valor zero"));
+                    }
+                    amount
+                } else {
+                    let amount = self.amount.ok_or_else(|| anyhow::anyhow!("Valor não definido"))?;
+                    if amount.is_zero() {
+                        return Err(anyhow::anyhow!("Valor deve ser maior que zero"));
+                    }
+                    amount
                 };
                 
+                // CONSTRÓI o próximo estado AQUI
+                let tip = self.tip.unwrap_or(Amount::ZERO);
+                if payment_type == PaymentType::Verification && !tip.is_zero() {
+                    return Err(anyhow::anyhow!("Verificação de conta não aceita gorjeta"));
+                }
+                let payment_info = PaymentInfo { amount, payment_type, tip };
+                
+                // PIX não usa o fluxo de chip
+                if payment_info.payment_type == PaymentType::Pix {
+                    let merchant = pix_merchant()
+                        .ok_or_else(|| anyhow::anyhow!("Recebedor PIX não configurado"))?;
+                    return Ok(Some((
+                        StateType::PixPayment,
+                        Box::new(PixPayment::new(payment_info, &merchant))
+                    )));
+                }
+                
+                let next_state = EMVPayment::new(payment_info)
+                    .with_require_processing(self.require_processing);
+                
                 Ok(Some((
                     StateType::EMVPayment,
                     Box::new(next_state)
@@ -95,6 +195,11 @@ impl PaymentState<AwaitingInfoAction> for AwaitingInfo {
             _ => "Aguardando informações do pagamento".to_string(),
         }
     }
+    
+    /// Só dados digitados; nada enviado ao adquirente depende deste estado
+    fn is_reversible(&self) -> bool {
+        true
+    }
 }
 
 impl AwaitingInfo {
@@ -103,6 +208,8 @@ impl AwaitingInfo {
         Self {
             amount: None,
             payment_type: None,
+            tip: None,
+            require_processing: true,
         }
     }
 }
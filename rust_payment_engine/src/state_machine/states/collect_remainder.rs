@@ -0,0 +1,137 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, FieldSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::PaymentInfo;
+use super::emv_payment::{EMVPayment, EmvResult};
+use super::payment_success::{ApprovalMode, PartialAuthorization, PaymentSuccess, SplitTender};
+use super::super::clock::default_clock;
+use crate::money::{from_cents, to_cents, Amount};
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Ações válidas no estado CollectRemainder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum CollectRemainderAction {
+    /// Recebe parte (ou todo) o restante em dinheiro
+    CollectCash { amount: f64 },
+    /// Cobra o restante em um segundo cartão
+    SecondCard,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::CollectRemainder,
+    actions: &[
+        ActionSchema { name: "CollectCash", params: &[FieldSchema { name: "amount", ty: "f64" }] },
+        ActionSchema { name: "SecondCard", params: &[] },
+    ],
+    transitions: &[StateType::PaymentSuccess, StateType::EMVPayment],
+};
+
+/// Estado de cobrança do restante após uma aprovação parcial
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectRemainder {
+    /// Informações do pagamento original (valor total solicitado)
+    pub payment_info: PaymentInfo,
+    /// Resultado da autorização parcial
+    pub partial_result: EmvResult,
+    /// Valor aprovado na autorização parcial
+    pub approved_amount: f64,
+    /// Valor que ainda falta cobrar
+    pub shortfall: f64,
+    /// Parcelas cobradas antes desta autorização (cartões anteriores e dinheiro)
+    #[serde(default)]
+    pub split_tender: SplitTender,
+}
+
+impl CollectRemainder {
+    /// Parcelas anteriores somadas à autorização parcial deste estado
+    fn split_tender_with_partial(&self) -> SplitTender {
+        let mut split_tender = self.split_tender.clone();
+        split_tender.partial_authorizations.push(PartialAuthorization {
+            result: self.partial_result.clone(),
+            approved_amount: self.approved_amount,
+        });
+        split_tender
+    }
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<CollectRemainderAction> for CollectRemainder {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: CollectRemainderAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            CollectRemainderAction::CollectCash { amount } => {
+                let cash = Amount::new(amount)?;
+                if cash.cents() <= 0 {
+                    return Err(anyhow::anyhow!("Valor deve ser maior que zero"));
+                }
+                
+                // Compara em centavos para evitar erros de ponto flutuante
+                let shortfall_cents = to_cents(self.shortfall);
+                if cash.cents() > shortfall_cents {
+                    return Err(anyhow::anyhow!(
+                        "Valor excede o restante de R$ {:.2}",
+                        self.shortfall
+                    ));
+                }
+                
+                self.split_tender.cash = Amount::new(from_cents(self.split_tender.cash.cents() + cash.cents()))?;
+                if cash.cents() < shortfall_cents {
+                    self.shortfall = from_cents(shortfall_cents - cash.cents());
+                    return Ok(None);
+                }
+                
+                // CONSTRÓI o próximo estado AQUI
+                let next_state = PaymentSuccess {
+                    payment_info: self.payment_info.clone(),
+                    result: self.partial_result.clone(),
+                    approval_mode: ApprovalMode::Online,
+                    tip_adjustments: Vec::new(),
+                    split_tender: self.split_tender_with_partial(),
+                    clock: default_clock(),
+                };
+                
+                Ok(Some((
+                    StateType::PaymentSuccess,
+                    Box::new(next_state)
+                )))
+            }
+            
+            CollectRemainderAction::SecondCard => {
+                // CONSTRÓI novo EMVPayment para o restante, mantendo a venda original
+                let next_state = EMVPayment::new(self.payment_info.clone())
+                    .with_split_tender(self.split_tender_with_partial());
+                
+                Ok(Some((
+                    StateType::EMVPayment,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::CollectRemainder
+    }
+    
+    fn description(&self) -> String {
+        format!(
+            "Aprovado parcialmente R$ {:.2} de R$ {:.2} - Restante: R$ {:.2}",
+            self.approved_amount,
+            self.payment_info.total(),
+            self.shortfall
+        )
+    }
+}
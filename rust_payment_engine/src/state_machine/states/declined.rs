@@ -0,0 +1,89 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, AwaitingInfo};
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Motivo da recusa informado pelo adquirente
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeclinedReason(pub String);
+
+impl std::fmt::Display for DeclinedReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Ações válidas no estado Declined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DeclinedAction {
+    /// Volta para AwaitingInfo com valor e tipo já preenchidos
+    Retry,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::Declined,
+    actions: &[
+        ActionSchema { name: "Retry", params: &[] },
+    ],
+    transitions: &[StateType::AwaitingInfo],
+};
+
+/// Estado final - autorização recusada pelo adquirente
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Declined {
+    pub payment_info: PaymentInfo,
+    pub reason: DeclinedReason,
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<DeclinedAction> for Declined {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: DeclinedAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            DeclinedAction::Retry => {
+                // CONSTRÓI o estado inicial AQUI, reaproveitando os dados
+                let next_state = AwaitingInfo {
+                    amount: Some(self.payment_info.amount),
+                    payment_type: Some(self.payment_info.payment_type.clone()),
+                    tip: Some(self.payment_info.tip),
+                    ..AwaitingInfo::initial()
+                };
+                
+                Ok(Some((
+                    StateType::AwaitingInfo,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::Declined
+    }
+    
+    fn is_terminal(&self) -> bool {
+        true
+    }
+    
+    fn description(&self) -> String {
+        format!(
+            "Pagamento recusado - Valor: {}, Motivo: {}",
+            self.payment_info.amount_label(),
+            self.reason
+        )
+    }
+}
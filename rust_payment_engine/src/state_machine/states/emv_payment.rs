@@ -1,7 +1,18 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use super::awaiting_info::{PaymentInfo, AwaitingInfo};
-use super::payment_success::PaymentSuccess;
+use super::super::schema::{ActionSchema, FieldSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, PaymentType, AwaitingInfo};
+use super::payment_success::{ApprovalMode, PaymentSuccess, SplitTender};
+use super::payment_failed::{PaymentFailed, FailureReason};
+use super::collect_remainder::CollectRemainder;
+use super::voided::Voided;
+use super::declined::{Declined, DeclinedReason};
+use crate::money::{from_cents, Amount};
+use super::super::config::{cancel_grace_window, cancel_reason_threshold, cardholder_confirmation_threshold};
+use super::super::clock::{default_clock, Clock};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
 
 // ==================== TYPES DESTE ESTADO ====================
 
@@ -13,23 +24,291 @@ pub struct EmvResult {
     pub timestamp: String,
 }
 
+impl EmvResult {
+    /// Inicia a construção validada de um `EmvResult`
+    /// 
+    /// # Exemplo
+    /// ```
+    /// let result = EmvResult::builder()
+    ///     .transaction_id("TXN123")
+    ///     .authorization_code("AUTH456")
+    ///     .build()?;
+    /// ```
+    pub fn builder() -> EmvResultBuilder {
+        EmvResultBuilder::default()
+    }
+    
+    /// Valida que os identificadores do resultado estão preenchidos
+    pub fn validate(&self) -> Result<()> {
+        if self.transaction_id.trim().is_empty() {
+            return Err(anyhow::anyhow!("ID da transação não pode ser vazio"));
+        }
+        if self.authorization_code.trim().is_empty() {
+            return Err(anyhow::anyhow!("Código de autorização não pode ser vazio"));
+        }
+        Ok(())
+    }
+}
+
+/// Builder de `EmvResult` com validação dos campos
+/// 
+/// O timestamp é o horário atual (RFC3339).
+#[derive(Debug, Clone, Default)]
+pub struct EmvResultBuilder {
+    transaction_id: Option<String>,
+    authorization_code: Option<String>,
+}
+
+impl EmvResultBuilder {
+    /// Define o ID da transação
+    pub fn transaction_id(mut self, transaction_id: impl Into<String>) -> Self {
+        self.transaction_id = Some(transaction_id.into());
+        self
+    }
+    
+    /// Define o código de autorização
+    pub fn authorization_code(mut self, authorization_code: impl Into<String>) -> Self {
+        self.authorization_code = Some(authorization_code.into());
+        self
+    }
+    
+    /// Constrói o resultado, validando os campos
+    pub fn build(self) -> Result<EmvResult> {
+        let result = EmvResult {
+            transaction_id: self.transaction_id.unwrap_or_default(),
+            authorization_code: self.authorization_code.unwrap_or_default(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        result.validate()?;
+        
+        Ok(result)
+    }
+}
+
 /// Ações válidas no estado EMVPayment
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum EmvPaymentAction {
     ProcessPayment,
-    CompletePayment { result: EmvResult },
-    CancelPayment,
+    /// Conclui o pagamento; com `result: None` usa o resultado armazenado via `UpdateResult`
+    CompletePayment { result: Option<EmvResult> },
+    /// Como `CompletePayment`, mas aprovado pelo terminal sem contato com o emissor
+    CompleteOfflineStandIn { result: Option<EmvResult> },
+    /// Substitui o resultado pendente (ex: autorização re-tentada) sem transicionar
+    UpdateResult { result: EmvResult },
+    /// Autorização aprovou apenas parte do valor solicitado
+    CompletePartial { result: EmvResult, approved_amount: f64 },
+    /// Cancela o pagamento; acima do limite configurado exige `reason`
+    /// 
+    /// Após o início do processamento só é permitido dentro da janela de
+    /// cancelamento; depois dela é preciso usar `VoidPayment`.
+    CancelPayment { reason: Option<String> },
+    /// Estorna (void) um pagamento cuja autorização já pode ter sido enviada
+    VoidPayment { reason: Option<String> },
+    FailPayment { reason: FailureReason },
+    /// Autorização recusada pelo adquirente
+    DeclinePayment { reason: String },
+    /// Progresso do processamento (0–100, nunca regride)
+    ReportProgress { percent: u8 },
+    /// Portador confirmou a venda (exigido acima do limite configurado)
+    ConfirmCardholder,
+    /// Falha na leitura do chip; após `MAX_CHIP_READ_ATTEMPTS` ativa a tarja magnética
+    ChipReadFailed,
 }
 
+/// Falhas de leitura do chip antes do fallback para tarja magnética
+pub const MAX_CHIP_READ_ATTEMPTS: u8 = 3;
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::EMVPayment,
+    actions: &[
+        ActionSchema { name: "ProcessPayment", params: &[] },
+        ActionSchema { name: "CompletePayment", params: &[FieldSchema { name: "result", ty: "Option<EmvResult>" }] },
+        ActionSchema { name: "CompleteOfflineStandIn", params: &[FieldSchema { name: "result", ty: "Option<EmvResult>" }] },
+        ActionSchema { name: "UpdateResult", params: &[FieldSchema { name: "result", ty: "EmvResult" }] },
+        ActionSchema { name: "CompletePartial", params: &[
+            FieldSchema { name: "result", ty: "EmvResult" },
+            FieldSchema { name: "approved_amount", ty: "f64" },
+        ] },
+        ActionSchema { name: "CancelPayment", params: &[FieldSchema { name: "reason", ty: "Option<String>" }] },
+        ActionSchema { name: "VoidPayment", params: &[FieldSchema { name: "reason", ty: "Option<String>" }] },
+        ActionSchema { name: "FailPayment", params: &[FieldSchema { name: "reason", ty: "FailureReason" }] },
+        ActionSchema { name: "DeclinePayment", params: &[FieldSchema { name: "reason", ty: "String" }] },
+        ActionSchema { name: "ReportProgress", params: &[FieldSchema { name: "percent", ty: "u8" }] },
+        ActionSchema { name: "ConfirmCardholder", params: &[] },
+        ActionSchema { name: "ChipReadFailed", params: &[] },
+    ],
+    transitions: &[
+        StateType::PaymentSuccess,
+        StateType::CollectRemainder,
+        StateType::Declined,
+        StateType::PaymentFailed,
+        StateType::AwaitingInfo,
+        StateType::Voided,
+    ],
+};
+
 // ==================== ESTADO ====================
 
 /// Estado de processamento do pagamento EMV
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EMVPayment {
     pub payment_info: PaymentInfo,
     pub processing: bool,
     pub emv_result: Option<EmvResult>,
+    /// Verificação de conta (valor zero): autoriza sem cobrar
+    pub verification_only: bool,
+    /// Momento em que ProcessPayment foi executado
+    pub processing_started_at: Option<DateTime<Utc>>,
+    /// Último progresso reportado (0–100)
+    pub progress: u8,
+    /// Exige ProcessPayment antes de CompletePayment (fluxos com chip)
+    pub require_processing: bool,
+    /// Portador confirmou explicitamente a venda
+    pub cardholder_confirmed: bool,
+    /// Falhas de leitura do chip registradas
+    #[serde(default)]
+    pub read_attempts: u8,
+    /// Chip ilegível: captura pela tarja magnética
+    #[serde(default)]
+    pub fallback: bool,
+    /// Transação original, quando esta é uma cobrança complementar
+    #[serde(default)]
+    pub original_transaction_id: Option<String>,
+    /// Parcelas já cobradas, quando este é o cartão do restante
+    #[serde(default)]
+    pub split_tender: SplitTender,
+    /// Fonte de tempo (injetável para testes; não é persistida)
+    #[serde(skip, default = "default_clock")]
+    pub clock: Arc<dyn Clock>,
+}
+
+impl EMVPayment {
+    /// Exige motivo não vazio para cancelar valores acima do limite configurado
+    fn check_cancel_reason(&self, reason: Option<&str>) -> Result<()> {
+        let has_reason = reason.is_some_and(|r| !r.trim().is_empty());
+        if self.payment_info.amount.value() > cancel_reason_threshold() && !has_reason {
+            return Err(anyhow::anyhow!(
+                "Cancelamento acima de R$ {:.2}: motivo obrigatório",
+                cancel_reason_threshold()
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Exige ConfirmCardholder para aprovar totais (com gorjeta) acima do limite configurado
+    fn check_cardholder_confirmation(&self) -> Result<()> {
+        let threshold = cardholder_confirmation_threshold();
+        if self.payment_info.total().value() > threshold && !self.cardholder_confirmed {
+            return Err(anyhow::anyhow!(
+                "Pagamento acima de R$ {:.2}: confirmação do portador obrigatória",
+                threshold
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Conclui com sucesso no modo de aprovação informado
+    fn complete(
+        &self,
+        result: Option<EmvResult>,
+        approval_mode: ApprovalMode,
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        if self.require_processing && !self.processing {
+            return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
+        }
+        self.check_cardholder_confirmation()?;
+        let result = result
+            .or_else(|| self.emv_result.clone())
+            .ok_or_else(|| anyhow::anyhow!("Nenhum resultado EMV disponível"))?;
+        result.validate()?;
+        
+        // CONSTRÓI o próximo estado AQUI
+        let next_state = PaymentSuccess {
+            payment_info: self.payment_info.clone(),
+            result,
+            approval_mode,
+            tip_adjustments: Vec::new(),
+            split_tender: self.split_tender.clone(),
+            clock: self.clock.clone(),
+        };
+        
+        Ok(Some((
+            super::super::StateType::PaymentSuccess,
+            Box::new(next_state)
+        )))
+    }
+    
+    /// Indica se já passou a janela em que CancelPayment é seguro
+    fn grace_window_expired(&self) -> bool {
+        let Some(started_at) = self.processing_started_at else {
+            return false;
+        };
+        let elapsed = (self.clock.now() - started_at).to_std().unwrap_or_default();
+        elapsed > cancel_grace_window()
+    }
+    
+    /// Construtor ao entrar no estado, ainda sem processamento
+    pub fn new(payment_info: PaymentInfo) -> Self {
+        let verification_only = payment_info.payment_type == PaymentType::Verification;
+        Self {
+            payment_info,
+            processing: false,
+            emv_result: None,
+            verification_only,
+            processing_started_at: None,
+            progress: 0,
+            require_processing: true,
+            cardholder_confirmed: false,
+            read_attempts: 0,
+            fallback: false,
+            original_transaction_id: None,
+            split_tender: SplitTender::default(),
+            clock: default_clock(),
+        }
+    }
+    
+    /// Define se CompletePayment exige processamento prévio
+    pub fn with_require_processing(mut self, require_processing: bool) -> Self {
+        self.require_processing = require_processing;
+        self
+    }
+    
+    /// Marca como cobrança complementar de `transaction_id`
+    pub fn with_original_transaction(mut self, transaction_id: String) -> Self {
+        self.original_transaction_id = Some(transaction_id);
+        self
+    }
+    
+    /// Registra as parcelas que já cobriram parte do valor
+    pub fn with_split_tender(mut self, split_tender: SplitTender) -> Self {
+        self.split_tender = split_tender;
+        self
+    }
+    
+    /// Valor ainda devido: o total menos as parcelas já cobradas
+    pub fn amount_due(&self) -> Amount {
+        let due_cents = self.payment_info.total().cents() - self.split_tender.covered_cents();
+        Amount::new(from_cents(due_cents.max(0))).unwrap_or(Amount::ZERO)
+    }
+    
+    /// Valor para exibição (o restante, em vendas divididas)
+    fn charge_label(&self) -> String {
+        if self.split_tender.is_empty() {
+            self.payment_info.amount_label()
+        } else {
+            format!("R$ {:.2} restante de R$ {:.2}", self.amount_due(), self.payment_info.total())
+        }
+    }
+    
+    /// Substitui a fonte de tempo
+    #[allow(dead_code)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 // ==================== IMPLEMENTAÇÃO DO TRAIT ====================
@@ -50,27 +329,132 @@ impl PaymentState<EmvPaymentAction> for EMVPayment {
                     return Err(anyhow::anyhow!("Pagamento já está sendo processado"));
                 }
                 self.processing = true;
+                self.processing_started_at = Some(self.clock.now());
                 Ok(None)
             }
             
             EmvPaymentAction::CompletePayment { result } => {
+                self.complete(result, ApprovalMode::Online)
+            }
+            
+            EmvPaymentAction::CompleteOfflineStandIn { result } => {
+                self.complete(result, ApprovalMode::OfflineStandIn)
+            }
+            
+            EmvPaymentAction::UpdateResult { result } => {
                 if !self.processing {
                     return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
                 }
+                result.validate()?;
+                self.emv_result = Some(result);
+                Ok(None)
+            }
+            
+            EmvPaymentAction::CompletePartial { result, approved_amount } => {
+                if !self.processing {
+                    return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
+                }
+                // Compara em centavos com o valor devido (inclui gorjeta)
+                let approved = Amount::new(approved_amount)?;
+                let due = self.amount_due();
+                if approved.cents() <= 0 || approved.cents() >= due.cents() {
+                    return Err(anyhow::anyhow!(
+                        "Valor aprovado parcial deve estar entre zero e R$ {:.2}",
+                        due
+                    ));
+                }
+                self.check_cardholder_confirmation()?;
+                result.validate()?;
                 
-                // CONSTRÓI o próximo estado AQUI
-                let next_state = PaymentSuccess {
+                // CONSTRÓI o estado de cobrança do restante AQUI
+                let next_state = CollectRemainder {
                     payment_info: self.payment_info.clone(),
-                    result,
+                    partial_result: result,
+                    approved_amount: from_cents(approved.cents()),
+                    shortfall: from_cents(due.cents() - approved.cents()),
+                    split_tender: self.split_tender.clone(),
                 };
                 
                 Ok(Some((
-                    StateType::PaymentSuccess,
+                    StateType::CollectRemainder,
                     Box::new(next_state)
                 )))
             }
             
-            EmvPaymentAction::CancelPayment => {
+            EmvPaymentAction::ReportProgress { percent } => {
+                if !self.processing {
+                    return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
+                }
+                if percent > 100 {
+                    return Err(anyhow::anyhow!("Progresso deve estar entre 0 e 100: {}", percent));
+                }
+                if percent < self.progress {
+                    return Err(anyhow::anyhow!(
+                        "Progresso não pode regredir ({} -> {})",
+                        self.progress,
+                        percent
+                    ));
+                }
+                self.progress = percent;
+                Ok(None)
+            }
+            
+            EmvPaymentAction::ChipReadFailed => {
+                if self.fallback {
+                    return Err(anyhow::anyhow!("Leitura já em fallback para tarja magnética"));
+                }
+                self.read_attempts += 1;
+                self.fallback = self.read_attempts >= MAX_CHIP_READ_ATTEMPTS;
+                Ok(None)
+            }
+            
+            EmvPaymentAction::ConfirmCardholder => {
+                self.cardholder_confirmed = true;
+                Ok(None)
+            }
+            
+            EmvPaymentAction::DeclinePayment { reason } => {
+                if !self.processing {
+                    return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
+                }
+                
+                // CONSTRÓI o estado recusado AQUI
+                let next_state = Declined {
+                    payment_info: self.payment_info.clone(),
+                    reason: DeclinedReason(reason),
+                };
+                
+                Ok(Some((
+                    StateType::Declined,
+                    Box::new(next_state)
+                )))
+            }
+            
+            EmvPaymentAction::FailPayment { reason } => {
+                if !self.processing {
+                    return Err(anyhow::anyhow!("Pagamento ainda não foi iniciado"));
+                }
+                
+                // CONSTRÓI o estado de falha AQUI
+                let next_state = PaymentFailed {
+                    payment_info: self.payment_info.clone(),
+                    reason,
+                };
+                
+                Ok(Some((
+                    StateType::PaymentFailed,
+                    Box::new(next_state)
+                )))
+            }
+            
+            EmvPaymentAction::CancelPayment { reason } => {
+                self.check_cancel_reason(reason.as_deref())?;
+                if self.grace_window_expired() {
+                    return Err(anyhow::anyhow!(
+                        "Janela de cancelamento expirada: use VoidPayment"
+                    ));
+                }
+                
                 // CONSTRÓI estado de retorno AQUI
                 let next_state = AwaitingInfo::initial();
                 
@@ -79,6 +463,27 @@ impl PaymentState<EmvPaymentAction> for EMVPayment {
                     Box::new(next_state)
                 )))
             }
+            
+            EmvPaymentAction::VoidPayment { reason } => {
+                if !self.processing {
+                    return Err(anyhow::anyhow!(
+                        "Pagamento ainda não foi iniciado: use CancelPayment"
+                    ));
+                }
+                self.check_cancel_reason(reason.as_deref())?;
+                
+                // CONSTRÓI o estado estornado AQUI
+                let next_state = Voided {
+                    payment_info: self.payment_info.clone(),
+                    reason,
+                    voided_at: self.clock.now().to_rfc3339(),
+                };
+                
+                Ok(Some((
+                    StateType::Voided,
+                    Box::new(next_state)
+                )))
+            }
         }
     }
     
@@ -87,10 +492,59 @@ impl PaymentState<EmvPaymentAction> for EMVPayment {
     }
     
     fn description(&self) -> String {
-        if self.processing {
-            format!("Processando pagamento de R$ {:.2}...", self.payment_info.amount)
+        if self.verification_only {
+            return if self.processing {
+                "Verificando conta (sem cobrança)...".to_string()
+            } else {
+                "Pronto para verificar conta (sem cobrança)".to_string()
+            };
+        }
+        let description = if self.processing {
+            format!("Processando pagamento de {}...", self.charge_label())
         } else {
-            format!("Pronto para processar pagamento de R$ {:.2}", self.payment_info.amount)
+            format!("Pronto para processar pagamento de {}", self.charge_label())
+        };
+        if self.fallback {
+            format!("{} (tarja magnética)", description)
+        } else {
+            description
         }
     }
+    
+    /// Entrada reversível apenas antes de o terminal iniciar a autorização
+    fn is_reversible(&self) -> bool {
+        !self.processing
+    }
+}
+
+#[cfg(test)]
+mod emv_result_tests {
+    use super::*;
+    
+    #[test]
+    fn test_builder_builds_valid_result_with_current_timestamp() {
+        let result = EmvResult::builder()
+            .transaction_id("TXN123")
+            .authorization_code("AUTH456")
+            .build()
+            .unwrap();
+        
+        assert_eq!(result.transaction_id, "TXN123");
+        assert_eq!(result.authorization_code, "AUTH456");
+        assert!(chrono::DateTime::parse_from_rfc3339(&result.timestamp).is_ok());
+    }
+    
+    #[test]
+    fn test_builder_rejects_empty_fields() {
+        let missing_auth = EmvResult::builder()
+            .transaction_id("TXN123")
+            .build();
+        assert!(missing_auth.is_err());
+        
+        let empty_id = EmvResult::builder()
+            .transaction_id("")
+            .authorization_code("AUTH456")
+            .build();
+        assert!(empty_id.unwrap_err().to_string().contains("ID da transação"));
+    }
 }
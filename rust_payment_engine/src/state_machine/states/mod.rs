@@ -1,17 +1,36 @@
 pub mod awaiting_info;
 pub mod emv_payment;
 pub mod payment_success;
+pub mod payment_failed;
+pub mod collect_remainder;
+pub mod voided;
+pub mod declined;
+pub mod refund;
+pub mod pix_payment;
 
 // Export estados
 pub use awaiting_info::AwaitingInfo;
 pub use emv_payment::EMVPayment;
 pub use payment_success::PaymentSuccess;
+pub use payment_failed::PaymentFailed;
+pub use collect_remainder::CollectRemainder;
+pub use voided::Voided;
+pub use declined::Declined;
+pub use refund::Refund;
+pub use pix_payment::PixPayment;
 
 // Export ações específicas
 pub use awaiting_info::AwaitingInfoAction;
 pub use emv_payment::EmvPaymentAction;
 pub use payment_success::PaymentSuccessAction;
+pub use payment_failed::PaymentFailedAction;
+pub use collect_remainder::CollectRemainderAction;
+pub use voided::VoidedAction;
+pub use declined::DeclinedAction;
+pub use refund::RefundAction;
+pub use pix_payment::PixPaymentAction;
 
 // Export types relacionados
 pub use awaiting_info::{PaymentType, PaymentInfo};
 pub use emv_payment::EmvResult;
+pub use payment_failed::FailureReason;
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, AwaitingInfo};
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Motivo da falha técnica do pagamento
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FailureReason {
+    /// Processamento excedeu o tempo máximo permitido
+    Timeout,
+}
+
+/// Ações válidas no estado PaymentFailed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum PaymentFailedAction {
+    Reset,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::PaymentFailed,
+    actions: &[
+        ActionSchema { name: "Reset", params: &[] },
+    ],
+    transitions: &[StateType::AwaitingInfo],
+};
+
+/// Estado final - pagamento falhou durante o processamento
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentFailed {
+    pub payment_info: PaymentInfo,
+    pub reason: FailureReason,
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<PaymentFailedAction> for PaymentFailed {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: PaymentFailedAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            PaymentFailedAction::Reset => {
+                // CONSTRÓI o estado inicial AQUI
+                let next_state = AwaitingInfo::initial();
+                
+                Ok(Some((
+                    StateType::AwaitingInfo,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::PaymentFailed
+    }
+    
+    fn is_terminal(&self) -> bool {
+        true
+    }
+    
+    fn description(&self) -> String {
+        let reason = match self.reason {
+            FailureReason::Timeout => "tempo limite de processamento excedido",
+        };
+        format!(
+            "Pagamento falhou: {} - Valor: R$ {:.2}",
+            reason,
+            self.payment_info.amount
+        )
+    }
+}
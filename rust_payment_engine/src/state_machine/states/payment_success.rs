@@ -1,22 +1,187 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use super::awaiting_info::{PaymentInfo, AwaitingInfo};
-use super::emv_payment::EmvResult;
+use super::super::schema::{ActionSchema, FieldSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, PaymentType, AwaitingInfo};
+use super::emv_payment::{EMVPayment, EmvResult};
+use super::refund::Refund;
+use super::super::clock::{default_clock, Clock};
+use crate::money::{from_cents, to_cents, Amount};
+use std::sync::Arc;
 
 // ==================== TYPES DESTE ESTADO ====================
 
+/// Prazo, a partir da aprovação, para ajustar a gorjeta
+pub const TIP_ADJUSTMENT_WINDOW_HOURS: i64 = 24;
+
+/// Acréscimo máximo sobre o total autorizado (20%) ao ajustar a gorjeta
+pub const MAX_TIP_UPLIFT_PERCENT: f64 = 20.0;
+
+/// Registro de um ajuste de gorjeta pós-autorização
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TipAdjustment {
+    pub previous_tip: Amount,
+    pub new_tip: Amount,
+    pub adjusted_at: String,
+}
+
+/// Autorização parcial obtida antes da conclusão (venda dividida entre cartões)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialAuthorization {
+    pub result: EmvResult,
+    pub approved_amount: f64,
+}
+
+/// Parcelas de uma venda dividida após aprovação parcial
+/// 
+/// A venda mantém o `PaymentInfo` original; o valor ainda devido é sempre
+/// `total() - covered_cents()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SplitTender {
+    /// Autorizações parciais, na ordem em que foram aprovadas
+    pub partial_authorizations: Vec<PartialAuthorization>,
+    /// Valor recebido em dinheiro
+    pub cash: Amount,
+}
+
+impl SplitTender {
+    /// Indica se nenhuma parcela foi cobrada ainda
+    pub fn is_empty(&self) -> bool {
+        self.partial_authorizations.is_empty() && self.cash.is_zero()
+    }
+    
+    /// Valor já coberto pelas parcelas, em centavos
+    pub fn covered_cents(&self) -> i64 {
+        let authorized: i64 = self.partial_authorizations
+            .iter()
+            .map(|authorization| to_cents(authorization.approved_amount))
+            .sum();
+        authorized + self.cash.cents()
+    }
+}
+
+/// Como a aprovação foi obtida
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ApprovalMode {
+    /// Autorizada pelo emissor
+    #[default]
+    Online,
+    /// Aprovada pelo terminal sem contato com o emissor (risco na liquidação)
+    OfflineStandIn,
+}
+
+impl std::fmt::Display for ApprovalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalMode::Online => write!(f, "online"),
+            ApprovalMode::OfflineStandIn => write!(f, "offline (stand-in)"),
+        }
+    }
+}
+
 /// Ações válidas no estado PaymentSuccess
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum PaymentSuccessAction {
     Reset,
+    /// Reembolsa total ou parcialmente (0 < amount <= valor original)
+    RefundPayment { amount: f64 },
+    /// Cobrança complementar no mesmo cartão (ex: item esquecido)
+    SupplementalCharge { amount: f64 },
+    /// Ajusta a gorjeta capturada (ex: cliente contesta só a gorjeta)
+    AdjustTip { new_tip: f64 },
 }
 
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::PaymentSuccess,
+    actions: &[
+        ActionSchema { name: "Reset", params: &[] },
+        ActionSchema { name: "RefundPayment", params: &[FieldSchema { name: "amount", ty: "f64" }] },
+        ActionSchema { name: "SupplementalCharge", params: &[FieldSchema { name: "amount", ty: "f64" }] },
+        ActionSchema { name: "AdjustTip", params: &[FieldSchema { name: "new_tip", ty: "f64" }] },
+    ],
+    transitions: &[StateType::AwaitingInfo, StateType::Refund, StateType::EMVPayment],
+};
+
 /// Estado final - pagamento concluído com sucesso
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentSuccess {
     pub payment_info: PaymentInfo,
     pub result: EmvResult,
+    /// Modo de aprovação, definido na conclusão
+    #[serde(default)]
+    pub approval_mode: ApprovalMode,
+    /// Ajustes de gorjeta feitos após a aprovação, em ordem
+    #[serde(default)]
+    pub tip_adjustments: Vec<TipAdjustment>,
+    /// Autorizações parciais e dinheiro, quando o valor foi dividido
+    #[serde(default)]
+    pub split_tender: SplitTender,
+    /// Fonte de tempo do prazo de ajuste (injetável para testes; não é persistida)
+    #[serde(skip, default = "default_clock")]
+    pub clock: Arc<dyn Clock>,
+}
+
+impl PaymentSuccess {
+    /// Substitui a fonte de tempo
+    #[allow(dead_code)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Total autorizado na aprovação (antes de qualquer ajuste de gorjeta)
+    fn authorized_total_cents(&self) -> i64 {
+        let original_tip = self
+            .tip_adjustments
+            .first()
+            .map_or(self.payment_info.tip, |adjustment| adjustment.previous_tip);
+        self.payment_info.amount.cents() + original_tip.cents()
+    }
+    
+    /// Troca a gorjeta e registra o ajuste, validando prazo, total e teto
+    fn adjust_tip(&mut self, new_tip: f64) -> Result<()> {
+        if !new_tip.is_finite() {
+            return Err(anyhow::anyhow!("Gorjeta inválida: {}", new_tip));
+        }
+        
+        let approved_at = chrono::DateTime::parse_from_rfc3339(&self.result.timestamp)
+            .map_err(|_| anyhow::anyhow!("Timestamp da aprovação inválido: {}", self.result.timestamp))?;
+        let now = self.clock.now();
+        if now.signed_duration_since(approved_at) > chrono::Duration::hours(TIP_ADJUSTMENT_WINDOW_HOURS) {
+            return Err(anyhow::anyhow!(
+                "Prazo de {}h para ajuste de gorjeta expirado",
+                TIP_ADJUSTMENT_WINDOW_HOURS
+            ));
+        }
+        
+        let new_total_cents = self.payment_info.amount.cents() + to_cents(new_tip);
+        if new_total_cents < 0 {
+            return Err(anyhow::anyhow!("Ajuste deixaria o total negativo"));
+        }
+        let new_tip = Amount::new(new_tip)?;
+        
+        let authorized_cents = self.authorized_total_cents();
+        let cap_cents = authorized_cents + to_cents(from_cents(authorized_cents) * MAX_TIP_UPLIFT_PERCENT / 100.0);
+        if new_total_cents > cap_cents {
+            return Err(anyhow::anyhow!(
+                "Total ajustado excede o teto de R$ {:.2} ({}% sobre o autorizado)",
+                from_cents(cap_cents),
+                MAX_TIP_UPLIFT_PERCENT
+            ));
+        }
+        
+        self.tip_adjustments.push(TipAdjustment {
+            previous_tip: self.payment_info.tip,
+            new_tip,
+            adjusted_at: now.to_rfc3339(),
+        });
+        self.payment_info.tip = new_tip;
+        
+        Ok(())
+    }
 }
 
 // ==================== IMPLEMENTAÇÃO DO TRAIT ====================
@@ -41,6 +206,68 @@ impl PaymentState<PaymentSuccessAction> for PaymentSuccess {
                     Box::new(next_state)
                 )))
             }
+            
+            PaymentSuccessAction::RefundPayment { amount } => {
+                let refund = Amount::new(amount)
+                    .ok()
+                    .filter(|a| a.cents() > 0)
+                    .ok_or_else(|| anyhow::anyhow!("Valor do reembolso deve ser maior que zero"))?;
+                // Teto: total capturado (com a gorjeta ajustada). Refund é
+                // terminal, então não há reembolso anterior a descontar.
+                let refundable = self.payment_info.total();
+                if refund.cents() > refundable.cents() {
+                    return Err(anyhow::anyhow!(
+                        "Reembolso não pode exceder o valor original de R$ {:.2}",
+                        refundable
+                    ));
+                }
+                
+                // CONSTRÓI o estado de reembolso AQUI
+                let next_state = Refund {
+                    payment_info: self.payment_info.clone(),
+                    original_result: self.result.clone(),
+                    amount: refund,
+                    refunded_at: self.clock.now().to_rfc3339(),
+                };
+                
+                Ok(Some((
+                    StateType::Refund,
+                    Box::new(next_state)
+                )))
+            }
+            
+            PaymentSuccessAction::SupplementalCharge { amount } => {
+                let amount = Amount::new(amount)
+                    .ok()
+                    .filter(|a| a.cents() > 0)
+                    .ok_or_else(|| anyhow::anyhow!("Valor da cobrança complementar deve ser maior que zero"))?;
+                // Verificação não cobra e Pix não tem cartão para reutilizar
+                if matches!(self.payment_info.payment_type, PaymentType::Verification | PaymentType::Pix) {
+                    return Err(anyhow::anyhow!(
+                        "Cobrança complementar não disponível para pagamentos {:?}",
+                        self.payment_info.payment_type
+                    ));
+                }
+                
+                // CONSTRÓI um novo EMVPayment referenciando a transação original
+                let next_state = EMVPayment::new(PaymentInfo {
+                    amount,
+                    payment_type: self.payment_info.payment_type.clone(),
+                    tip: Amount::ZERO,
+                })
+                .with_original_transaction(self.result.transaction_id.clone());
+                
+                Ok(Some((
+                    StateType::EMVPayment,
+                    Box::new(next_state)
+                )))
+            }
+            
+            PaymentSuccessAction::AdjustTip { new_tip } => {
+                // Permanece em PaymentSuccess com o total recalculado
+                self.adjust_tip(new_tip)?;
+                Ok(None)
+            }
         }
     }
     
@@ -48,12 +275,17 @@ impl PaymentState<PaymentSuccessAction> for PaymentSuccess {
         super::super::StateType::PaymentSuccess
     }
     
+    fn is_terminal(&self) -> bool {
+        true
+    }
+    
     fn description(&self) -> String {
         format!(
-            "Pagamento concluído com sucesso - ID: {}, Código: {}, Valor: R$ {:.2}",
+            "Pagamento concluído com sucesso - ID: {}, Código: {}, Valor: {}, Aprovação: {}",
             self.result.transaction_id,
             self.result.authorization_code,
-            self.payment_info.amount
+            self.payment_info.amount_label(),
+            self.approval_mode
         )
     }
 }
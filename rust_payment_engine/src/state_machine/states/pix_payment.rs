@@ -0,0 +1,237 @@
+use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, FieldSchema, StateSchema};
+use super::super::StateType;
+use super::super::config::PixMerchant;
+use super::awaiting_info::{PaymentInfo, AwaitingInfo};
+use super::emv_payment::EmvResult;
+use super::payment_success::{ApprovalMode, PaymentSuccess, SplitTender};
+use super::super::clock::default_clock;
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Ações válidas no estado PixPayment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum PixPaymentAction {
+    /// PSP confirmou o recebimento (identificador fim a fim da transferência)
+    ConfirmPixReceived { end_to_end_id: String },
+    /// Desiste do PIX e volta para AwaitingInfo
+    CancelPayment,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::PixPayment,
+    actions: &[
+        ActionSchema { name: "ConfirmPixReceived", params: &[FieldSchema { name: "end_to_end_id", ty: "String" }] },
+        ActionSchema { name: "CancelPayment", params: &[] },
+    ],
+    transitions: &[StateType::PaymentSuccess, StateType::AwaitingInfo],
+};
+
+/// Comprimento do txid gerado (máximo permitido pelo BR Code é 25)
+const TXID_LENGTH: usize = 25;
+
+/// Campo TLV do BR Code: id, tamanho com 2 dígitos e valor
+fn tlv(id: &str, value: &str) -> String {
+    format!("{}{:02}{}", id, value.len(), value)
+}
+
+/// CRC16-CCITT (polinômio 0x1021, inicial 0xFFFF), exigido no campo 63
+fn crc16(payload: &str) -> u16 {
+    payload.bytes().fold(0xFFFF, |crc, byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Monta o payload "copia e cola" (BR Code) para o recebedor, valor e txid
+fn qr_payload(merchant: &PixMerchant, payment_info: &PaymentInfo, txid: &str) -> String {
+    let account = tlv("00", "br.gov.bcb.pix") + &tlv("01", &merchant.key);
+    let mut payload = String::new();
+    payload.push_str(&tlv("00", "01"));
+    payload.push_str(&tlv("26", &account));
+    payload.push_str(&tlv("52", "0000"));
+    payload.push_str(&tlv("53", "986"));
+    payload.push_str(&tlv("54", &format!("{:.2}", payment_info.total())));
+    payload.push_str(&tlv("58", "BR"));
+    payload.push_str(&tlv("59", &merchant.name));
+    payload.push_str(&tlv("60", &merchant.city));
+    payload.push_str(&tlv("62", &tlv("05", txid)));
+    payload.push_str("6304");
+    let crc = crc16(&payload);
+    payload.push_str(&format!("{:04X}", crc));
+    payload
+}
+
+/// Estado de espera do pagamento PIX (QR exibido ao cliente)
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixPayment {
+    pub payment_info: PaymentInfo,
+    /// Identificador da cobrança, embutido no QR
+    pub txid: String,
+    /// Payload do QR code (BR Code)
+    pub qr_payload: String,
+}
+
+impl PixPayment {
+    /// Construtor ao entrar no estado: gera txid e payload do QR
+    pub fn new(payment_info: PaymentInfo, merchant: &PixMerchant) -> Self {
+        let txid: String = rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(TXID_LENGTH)
+            .map(char::from)
+            .collect();
+        let qr_payload = qr_payload(merchant, &payment_info, &txid);
+        Self { payment_info, txid, qr_payload }
+    }
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<PixPaymentAction> for PixPayment {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: PixPaymentAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            PixPaymentAction::ConfirmPixReceived { end_to_end_id } => {
+                if end_to_end_id.trim().is_empty() {
+                    return Err(anyhow::anyhow!("Identificador fim a fim do PIX obrigatório"));
+                }
+                
+                // CONSTRÓI o estado de sucesso AQUI
+                let next_state = PaymentSuccess {
+                    payment_info: self.payment_info.clone(),
+                    result: EmvResult {
+                        transaction_id: self.txid.clone(),
+                        authorization_code: end_to_end_id,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    },
+                    approval_mode: ApprovalMode::Online,
+                    tip_adjustments: Vec::new(),
+                    split_tender: SplitTender::default(),
+                    clock: default_clock(),
+                };
+                
+                Ok(Some((
+                    StateType::PaymentSuccess,
+                    Box::new(next_state)
+                )))
+            }
+            
+            PixPaymentAction::CancelPayment => {
+                // CONSTRÓI estado de retorno AQUI
+                let next_state = AwaitingInfo::initial();
+                
+                Ok(Some((
+                    StateType::AwaitingInfo,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::PixPayment
+    }
+    
+    fn description(&self) -> String {
+        format!(
+            "Aguardando PIX - Valor: {}, txid: {}",
+            self.payment_info.amount_label(),
+            self.txid
+        )
+    }
+}
+
+#[cfg(test)]
+mod pix_payment_tests {
+    use super::*;
+    use super::super::awaiting_info::PaymentType;
+    use crate::money::Amount;
+
+    fn merchant() -> PixMerchant {
+        PixMerchant {
+            key: "loja@example.com".to_string(),
+            name: "LOJA EXEMPLO".to_string(),
+            city: "SAO PAULO".to_string(),
+        }
+    }
+
+    fn pix(amount: f64) -> PixPayment {
+        PixPayment::new(PaymentInfo {
+            amount: Amount::new(amount).unwrap(),
+            payment_type: PaymentType::Pix,
+            tip: Amount::ZERO,
+        }, &merchant())
+    }
+
+    #[test]
+    fn test_qr_payload_carries_amount_and_txid() {
+        let state = pix(100.0);
+        
+        assert_eq!(state.txid.len(), TXID_LENGTH);
+        assert!(state.qr_payload.starts_with("000201"));
+        assert!(state.qr_payload.contains("5406100.00"));
+        assert!(state.qr_payload.contains(&format!("0525{}", state.txid)));
+    }
+
+    #[test]
+    fn test_qr_payload_carries_merchant_fields() {
+        let state = pix(10.0);
+        
+        assert!(state.qr_payload.contains("26380014br.gov.bcb.pix0116loja@example.com"));
+        assert!(state.qr_payload.contains("5912LOJA EXEMPLO"));
+        assert!(state.qr_payload.contains("6009SAO PAULO"));
+    }
+
+    #[test]
+    fn test_qr_payload_ends_with_valid_crc() {
+        let state = pix(12.34);
+        let (body, crc) = state.qr_payload.split_at(state.qr_payload.len() - 4);
+        
+        assert!(body.ends_with("6304"));
+        assert_eq!(crc, format!("{:04X}", crc16(body)));
+    }
+
+    #[test]
+    fn test_description_shows_tip() {
+        let state = PixPayment::new(PaymentInfo {
+            amount: Amount::new(100.0).unwrap(),
+            payment_type: PaymentType::Pix,
+            tip: Amount::new(10.0).unwrap(),
+        }, &merchant());
+        
+        assert!(state.description().contains("R$ 100.00 + Gorjeta R$ 10.00"));
+    }
+
+    #[test]
+    fn test_crc16_matches_reference_value() {
+        // Valor de referência do CRC-16/CCITT-FALSE
+        assert_eq!(crc16("123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_confirm_requires_end_to_end_id() {
+        let mut state = pix(10.0);
+        
+        let result = state.execute_action_with_transition(PixPaymentAction::ConfirmPixReceived {
+            end_to_end_id: " ".to_string(),
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, AwaitingInfo};
+use super::emv_payment::EmvResult;
+use crate::money::Amount;
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Ações válidas no estado Refund
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum RefundAction {
+    Reset,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::Refund,
+    actions: &[
+        ActionSchema { name: "Reset", params: &[] },
+    ],
+    transitions: &[StateType::AwaitingInfo],
+};
+
+/// Estado final - pagamento concluído e depois reembolsado (total ou parcial)
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Refund {
+    /// Informações do pagamento original
+    pub payment_info: PaymentInfo,
+    /// Resultado da autorização original
+    pub original_result: EmvResult,
+    /// Valor reembolsado (até o valor original)
+    pub amount: Amount,
+    /// Momento do reembolso (RFC3339)
+    pub refunded_at: String,
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<RefundAction> for Refund {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: RefundAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            RefundAction::Reset => {
+                // CONSTRÓI o estado inicial AQUI
+                let next_state = AwaitingInfo::initial();
+                
+                Ok(Some((
+                    StateType::AwaitingInfo,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::Refund
+    }
+    
+    fn is_terminal(&self) -> bool {
+        true
+    }
+    
+    fn description(&self) -> String {
+        format!(
+            "Reembolso de R$ {:.2} de R$ {:.2} - ID original: {}",
+            self.amount,
+            self.payment_info.total(),
+            self.original_result.transaction_id
+        )
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::super::schema::{ActionSchema, StateSchema};
+use super::super::StateType;
+use super::awaiting_info::{PaymentInfo, AwaitingInfo};
+
+// ==================== TYPES DESTE ESTADO ====================
+
+/// Ações válidas no estado Voided
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum VoidedAction {
+    Reset,
+}
+
+/// Metadados deste estado para `export_machine_schema`
+pub const SCHEMA: StateSchema = StateSchema {
+    state: StateType::Voided,
+    actions: &[
+        ActionSchema { name: "Reset", params: &[] },
+    ],
+    transitions: &[StateType::AwaitingInfo],
+};
+
+/// Estado final - pagamento estornado (void) após início da autorização
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Voided {
+    pub payment_info: PaymentInfo,
+    pub reason: Option<String>,
+    pub voided_at: String,
+}
+
+// ==================== IMPLEMENTAÇÃO DO TRAIT ====================
+
+use super::super::state_trait::PaymentState;
+
+impl PaymentState<VoidedAction> for Voided {
+    /// Executa ação - CONSTRÓI próximo estado se houver transição
+    fn execute_action_with_transition(
+        &mut self, 
+        action: VoidedAction
+    ) -> Result<Option<(super::super::StateType, Box<dyn std::any::Any + Send + Sync>)>> {
+        use super::super::StateType;
+        
+        match action {
+            VoidedAction::Reset => {
+                // CONSTRÓI o estado inicial AQUI
+                let next_state = AwaitingInfo::initial();
+                
+                Ok(Some((
+                    StateType::AwaitingInfo,
+                    Box::new(next_state)
+                )))
+            }
+        }
+    }
+    
+    fn state_type(&self) -> super::super::StateType {
+        super::super::StateType::Voided
+    }
+    
+    fn is_terminal(&self) -> bool {
+        true
+    }
+    
+    fn description(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!(
+                "Pagamento estornado - Valor: {}, Motivo: {}",
+                self.payment_info.amount_label(),
+                reason
+            ),
+            None => format!("Pagamento estornado - Valor: {}", self.payment_info.amount_label()),
+        }
+    }
+}
@@ -6,6 +6,15 @@ pub enum StateType {
     AwaitingInfo,
     EMVPayment,
     PaymentSuccess,
+    PaymentFailed,
+    CollectRemainder,
+    Voided,
+    Declined,
+    Refund,
+    PixPayment,
+    /// Estado fictício, usado apenas nos testes do registry
+    #[cfg(test)]
+    TestDummy,
 }
 
 /// Evento de mudança de estado para enviar ao Flutter
@@ -14,14 +23,93 @@ pub struct StateChangeEvent {
     pub from_state: StateType,
     pub to_state: StateType,
     pub timestamp: String,
+    /// Número sequencial do evento (monotônico por StateManager, começa em 1)
+    pub seq: u64,
+    /// Indica que uma transição posterior tornou este evento obsoleto
+    #[serde(default)]
+    pub superseded: bool,
+}
+
+/// Evento do fluxo: mudança de estado ou falha de uma ação
+/// 
+/// Canal opcional, separado de `StateChangeEvent`, para UIs que também
+/// precisam reagir a erros de validação (ex: exibir um toast).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum StateEvent {
+    /// Transição realizada
+    Changed(StateChangeEvent),
+    /// Ação rejeitada; o estado permanece o mesmo
+    Failed {
+        state: StateType,
+        action: String,
+        error: String,
+        timestamp: String,
+    },
+}
+
+/// Estado atual serializado, para persistir o fluxo entre execuções do app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedState {
+    pub state_type: StateType,
+    /// Dados do estado concreto (formato definido pelo próprio estado)
+    pub state: serde_json::Value,
+}
+
+/// Resultado de uma ação executada pelo StateManager
+/// 
+/// `Display` reproduz as mensagens antigas ("Transicionado para ...").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionOutcome {
+    pub transitioned: bool,
+    /// Estado após a ação (igual a `previous_state` se não houve transição)
+    pub new_state: StateType,
+    pub previous_state: StateType,
+}
+
+impl std::fmt::Display for TransitionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.transitioned {
+            write!(f, "Transicionado para {:?}", self.new_state)
+        } else {
+            write!(f, "Ação executada - permanece no mesmo estado")
+        }
+    }
+}
+
+/// Resultado de uma ação recebida como JSON (`execute_json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    /// Estado em que a ação foi executada
+    pub from_state: StateType,
+    /// Estado após a ação (igual a `from_state` se não houve transição)
+    pub state: StateType,
+    pub transitioned: bool,
+    pub message: String,
+}
+
+/// Registro de uma transição na linha do tempo do fluxo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionRecord {
+    pub from: StateType,
+    pub to: StateType,
+    /// Momento da transição (RFC3339)
+    pub at: String,
+    /// Tempo de permanência no estado `from` antes da transição
+    pub duration_ms: u64,
 }
 
 /// Enum unificado de todas as ações possíveis
 /// 
 /// Cada estado tem suas ações, mas precisamos de um tipo unificado
 /// para o StateManager ser genérico
+/// 
+/// Serializa no formato adjacente `{"type": ..., "data": ...}`, o mesmo
+/// usado pelas ações de cada estado, ex:
+/// `{"type":"AwaitingInfo","data":{"type":"SetAmount","data":{"amount":100.0}}}`
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum StateAction {
     /// Ações do estado AwaitingInfo
     AwaitingInfo(crate::state_machine::states::AwaitingInfoAction),
@@ -29,4 +117,122 @@ pub enum StateAction {
     EmvPayment(crate::state_machine::states::EmvPaymentAction),
     /// Ações do estado PaymentSuccess
     PaymentSuccess(crate::state_machine::states::PaymentSuccessAction),
+    /// Ações do estado PaymentFailed
+    PaymentFailed(crate::state_machine::states::PaymentFailedAction),
+    /// Ações do estado CollectRemainder
+    CollectRemainder(crate::state_machine::states::CollectRemainderAction),
+    /// Ações do estado Voided
+    Voided(crate::state_machine::states::VoidedAction),
+    /// Ações do estado Declined
+    Declined(crate::state_machine::states::DeclinedAction),
+    /// Ações do estado Refund
+    Refund(crate::state_machine::states::RefundAction),
+    /// Ações do estado PixPayment
+    PixPayment(crate::state_machine::states::PixPaymentAction),
+}
+
+#[cfg(test)]
+mod types_tests {
+    use super::*;
+    use crate::state_machine::states::*;
+    use serde_json::json;
+
+    /// Serializa, desserializa e serializa de novo; as duas formas devem coincidir
+    fn assert_round_trip(action: StateAction) -> serde_json::Value {
+        let encoded = serde_json::to_value(&action).unwrap();
+        let decoded: StateAction = serde_json::from_value(encoded.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), encoded);
+        encoded
+    }
+
+    fn emv_result() -> EmvResult {
+        EmvResult {
+            transaction_id: "TXN123".to_string(),
+            authorization_code: "AUTH456".to_string(),
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_inner_action_uses_type_and_data() {
+        let encoded = serde_json::to_value(AwaitingInfoAction::SetAmount { amount: 100.0 }).unwrap();
+        assert_eq!(encoded, json!({"type": "SetAmount", "data": {"amount": 100.0}}));
+
+        let decoded: AwaitingInfoAction =
+            serde_json::from_value(json!({"type": "SetAmount", "data": {"amount": 100}})).unwrap();
+        assert!(matches!(decoded, AwaitingInfoAction::SetAmount { amount } if amount == 100.0));
+
+        let unit = serde_json::to_value(AwaitingInfoAction::ConfirmInfo).unwrap();
+        assert_eq!(unit, json!({"type": "ConfirmInfo"}));
+    }
+
+    #[test]
+    fn test_state_action_nests_tagged_form() {
+        let encoded = assert_round_trip(StateAction::AwaitingInfo(
+            AwaitingInfoAction::SetAmount { amount: 100.0 },
+        ));
+        assert_eq!(
+            encoded,
+            json!({"type": "AwaitingInfo", "data": {"type": "SetAmount", "data": {"amount": 100.0}}})
+        );
+    }
+
+    #[test]
+    fn test_round_trip_awaiting_info_actions() {
+        assert_round_trip(StateAction::AwaitingInfo(AwaitingInfoAction::SetAmount { amount: 42.5 }));
+        assert_round_trip(StateAction::AwaitingInfo(AwaitingInfoAction::SetPaymentType {
+            payment_type: PaymentType::Credit,
+        }));
+        assert_round_trip(StateAction::AwaitingInfo(AwaitingInfoAction::SetTip { tip: 10.0 }));
+        assert_round_trip(StateAction::AwaitingInfo(AwaitingInfoAction::SetRequireProcessing {
+            require_processing: false,
+        }));
+        assert_round_trip(StateAction::AwaitingInfo(AwaitingInfoAction::ConfirmInfo));
+    }
+
+    #[test]
+    fn test_round_trip_emv_payment_actions() {
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::ProcessPayment));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::CompletePayment {
+            result: Some(emv_result()),
+        }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::CompletePayment { result: None }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::CompleteOfflineStandIn { result: None }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::UpdateResult { result: emv_result() }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::CompletePartial {
+            result: emv_result(),
+            approved_amount: 60.0,
+        }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::CancelPayment {
+            reason: Some("Cliente desistiu".to_string()),
+        }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::VoidPayment { reason: None }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::FailPayment {
+            reason: FailureReason::Timeout,
+        }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::DeclinePayment {
+            reason: "51 - Saldo insuficiente".to_string(),
+        }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::ReportProgress { percent: 40 }));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::ConfirmCardholder));
+        assert_round_trip(StateAction::EmvPayment(EmvPaymentAction::ChipReadFailed));
+    }
+
+    #[test]
+    fn test_round_trip_remaining_actions() {
+        assert_round_trip(StateAction::PaymentSuccess(PaymentSuccessAction::Reset));
+        assert_round_trip(StateAction::PaymentSuccess(PaymentSuccessAction::RefundPayment { amount: 25.0 }));
+        assert_round_trip(StateAction::PaymentSuccess(PaymentSuccessAction::SupplementalCharge { amount: 12.5 }));
+        assert_round_trip(StateAction::PaymentSuccess(PaymentSuccessAction::AdjustTip { new_tip: 5.0 }));
+        assert_round_trip(StateAction::Refund(RefundAction::Reset));
+        assert_round_trip(StateAction::PaymentFailed(PaymentFailedAction::Reset));
+        assert_round_trip(StateAction::CollectRemainder(CollectRemainderAction::CollectCash { amount: 15.0 }));
+        assert_round_trip(StateAction::CollectRemainder(CollectRemainderAction::SecondCard));
+        assert_round_trip(StateAction::Voided(VoidedAction::Reset));
+        assert_round_trip(StateAction::Declined(DeclinedAction::Retry));
+        assert_round_trip(StateAction::PixPayment(PixPaymentAction::ConfirmPixReceived {
+            end_to_end_id: "E12345678202401011200abcdefghijk".to_string(),
+        }));
+        assert_round_trip(StateAction::PixPayment(PixPaymentAction::CancelPayment));
+    }
 }
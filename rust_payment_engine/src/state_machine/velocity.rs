@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use super::clock::{Clock, SystemClock};
+
+/// Código de recusa quando a regra de velocidade é excedida
+pub const VELOCITY_EXCEEDED: &str = "velocity_exceeded";
+
+/// Máximo padrão de autorizações dentro da janela
+pub const DEFAULT_MAX_AUTHORIZATIONS: u32 = 5;
+
+/// Janela padrão da regra de velocidade
+pub const DEFAULT_VELOCITY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Regra de velocidade: no máximo `max_authorizations` dentro de `window`
+/// 
+/// Protege contra ataques de teste de cartão (muitas autorizações pequenas
+/// em sequência), independentemente do valor de cada uma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VelocityRule {
+    pub max_authorizations: u32,
+    pub window: Duration,
+}
+
+impl Default for VelocityRule {
+    fn default() -> Self {
+        Self {
+            max_authorizations: DEFAULT_MAX_AUTHORIZATIONS,
+            window: DEFAULT_VELOCITY_WINDOW,
+        }
+    }
+}
+
+/// Contador de autorizações da sessão em janela deslizante
+#[derive(Debug)]
+pub struct VelocityTracker {
+    rule: VelocityRule,
+    /// Instantes das autorizações ainda dentro da janela (mais antiga primeiro)
+    recent: VecDeque<DateTime<Utc>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl VelocityTracker {
+    /// Cria o contador com a regra informada e o relógio do sistema
+    pub fn new(rule: VelocityRule) -> Self {
+        Self::with_clock(rule, Arc::new(SystemClock))
+    }
+    
+    /// Cria o contador com uma fonte de tempo injetada
+    pub fn with_clock(rule: VelocityRule, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            rule,
+            recent: VecDeque::new(),
+            clock,
+        }
+    }
+    
+    /// Substitui a regra (o histórico da janela é mantido)
+    pub fn set_rule(&mut self, rule: VelocityRule) {
+        self.rule = rule;
+    }
+    
+    /// Recusa com `VELOCITY_EXCEEDED` se uma nova autorização excederia a regra
+    pub fn check(&mut self) -> Result<()> {
+        self.prune();
+        if self.recent.len() >= self.rule.max_authorizations as usize {
+            return Err(anyhow::anyhow!(
+                "{}: limite de {} autorizações em {}s atingido",
+                VELOCITY_EXCEEDED,
+                self.rule.max_authorizations,
+                self.rule.window.as_secs()
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Verifica e registra em uma única operação, retornando a vaga reservada
+    /// 
+    /// Sob o mesmo lock, duas chamadas concorrentes não passam ambas pela
+    /// verificação com a última vaga livre.
+    pub fn reserve(&mut self) -> Result<DateTime<Utc>> {
+        self.check()?;
+        let reserved_at = self.clock.now();
+        self.recent.push_back(reserved_at);
+        Ok(reserved_at)
+    }
+    
+    /// Devolve uma vaga reservada (a autorização não chegou a ser enviada)
+    pub fn release(&mut self, reserved_at: DateTime<Utc>) {
+        if let Some(index) = self.recent.iter().rposition(|&at| at == reserved_at) {
+            self.recent.remove(index);
+        }
+    }
+    
+    /// Descarta autorizações fora da janela
+    fn prune(&mut self) {
+        let now = self.clock.now();
+        while let Some(&oldest) = self.recent.front() {
+            let age = (now - oldest).to_std().unwrap_or_default();
+            if age < self.rule.window {
+                break;
+            }
+            self.recent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod velocity_tests {
+    use super::*;
+    use crate::state_machine::clock::ManualClock;
+
+    fn tracker(max_authorizations: u32, window_secs: u64) -> (VelocityTracker, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(Utc::now()));
+        let rule = VelocityRule {
+            max_authorizations,
+            window: Duration::from_secs(window_secs),
+        };
+        (VelocityTracker::with_clock(rule, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_rapid_authorizations_trip_the_rule() {
+        let (mut tracker, clock) = tracker(3, 60);
+        
+        for _ in 0..3 {
+            tracker.reserve().unwrap();
+            clock.advance(chrono::Duration::seconds(1));
+        }
+        
+        let error = tracker.check().unwrap_err().to_string();
+        assert!(error.starts_with(VELOCITY_EXCEEDED));
+    }
+
+    #[test]
+    fn test_spaced_authorizations_do_not_trip_the_rule() {
+        let (mut tracker, clock) = tracker(3, 60);
+        
+        for _ in 0..10 {
+            tracker.reserve().unwrap();
+            clock.advance(chrono::Duration::seconds(30));
+        }
+    }
+
+    #[test]
+    fn test_reserve_takes_slot_and_release_returns_it() {
+        let (mut tracker, _clock) = tracker(2, 60);
+        
+        tracker.reserve().unwrap();
+        let second = tracker.reserve().unwrap();
+        let error = tracker.reserve().unwrap_err().to_string();
+        assert!(error.starts_with(VELOCITY_EXCEEDED));
+        
+        tracker.release(second);
+        tracker.reserve().unwrap();
+        assert!(tracker.check().is_err());
+    }
+
+    #[test]
+    fn test_rule_recovers_after_window_passes() {
+        let (mut tracker, clock) = tracker(2, 10);
+        
+        tracker.reserve().unwrap();
+        tracker.reserve().unwrap();
+        assert!(tracker.check().is_err());
+        
+        clock.advance(chrono::Duration::seconds(10));
+        assert!(tracker.check().is_ok());
+    }
+}